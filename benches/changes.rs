@@ -1,7 +1,7 @@
 use life::{Automata, LifeLike};
 
 use bitvec::prelude::*;
-use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
 use rand::{thread_rng, Rng};
 
 fn criterion_benchmark(c: &mut Criterion) {
@@ -24,9 +24,9 @@ fn criterion_benchmark(c: &mut Criterion) {
                 .map(|(&a, &b)| a ^ b),
         );
         let machine = LifeLike::new("B3/S23").unwrap();
-        machine.update(&mut buffer1, &mut buffer2, &change_buffer, (*size, *size));
+        machine.update(&buffer1, &mut buffer2, &change_buffer, (*size, *size));
 
-        group.bench_with_input(BenchmarkId::from_parameter(size), size, |b, &size| {
+        group.bench_with_input(BenchmarkId::from_parameter(size), size, |b, _| {
             b.iter_batched_ref(
                 || (buffer1.clone(), buffer2.clone(), change_buffer.clone()),
                 |(buffer1, buffer2, change_buffer)| {
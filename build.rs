@@ -0,0 +1,19 @@
+use std::{env, fs, path::Path};
+
+/// Lists the files under `assets/pattern` so the viewer can offer them in its
+/// pattern browser, including on the web where directories can't be read.
+fn main() {
+    println!("cargo:rerun-if-changed=assets/pattern");
+
+    let mut names: Vec<String> = fs::read_dir("assets/pattern")
+        .expect("could not read assets/pattern")
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_file())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect();
+    names.sort();
+
+    let list = format!("pub const PATTERNS: &[&str] = &{:?};\n", names);
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join("patterns.rs");
+    fs::write(out, list).expect("could not write the pattern list");
+}
@@ -0,0 +1,142 @@
+//! Tracks how long each cell of a [`Grid`] has been alive, or how long ago it
+//! died, for coloring cells by their history.
+//!
+//! A single counter per cell is enough for both: it counts generations since
+//! the cell last changed, and the cell's current state says which of the two
+//! it means.
+
+use bitvec::prelude::*;
+
+use crate::grid::Grid;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Age {
+    /// Alive for this many generations since the one it was born in.
+    Alive(u16),
+    /// Dead for this many generations since the one it died in.
+    Dead(u16),
+    /// Not alive since tracking started, or so long ago that the count ran
+    /// out.
+    Empty,
+}
+
+#[derive(Clone, Debug)]
+pub struct Ages {
+    size: (usize, usize),
+    generation: usize,
+    alive: BitVec<Lsb0, usize>,
+    counters: Vec<u16>,
+}
+
+impl Ages {
+    /// Starts tracking at the grid's current state, treating every live cell
+    /// as newly born.
+    pub fn new(grid: &Grid) -> Self {
+        Ages {
+            size: grid.size(),
+            generation: grid.generation(),
+            alive: grid.cells().to_bitvec(),
+            counters: grid
+                .cells()
+                .iter()
+                .map(|alive| if *alive { 0 } else { u16::MAX })
+                .collect(),
+        }
+    }
+
+    /// Catches up with the grid. Call this after every step, and after edits.
+    ///
+    /// Cells that changed since the last update count as born or died; if the
+    /// grid also moved forward a generation, every other cell ages by one.
+    /// Stepping backwards doesn't rewind ages, and a world that changed size
+    /// starts over.
+    pub fn update(&mut self, grid: &Grid) {
+        if grid.size() != self.size {
+            *self = Ages::new(grid);
+            return;
+        }
+
+        let stepped = grid.generation() > self.generation;
+        for ((counter, was), now) in self
+            .counters
+            .iter_mut()
+            .zip(self.alive.iter_mut())
+            .zip(grid.cells().iter().by_val())
+        {
+            if *was != now {
+                *counter = 0;
+                was.set(now);
+            } else if stepped && (now || *counter != u16::MAX) {
+                // live cells stop at the limit; dead ones become empty
+                *counter = counter.saturating_add(1);
+            }
+        }
+
+        self.generation = grid.generation();
+    }
+
+    pub fn get(&self, cell: (usize, usize)) -> Age {
+        self.at(cell.0 + (cell.1 * self.size.0))
+    }
+
+    /// Every cell's age, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = Age> + '_ {
+        (0..self.counters.len()).map(move |index| self.at(index))
+    }
+
+    fn at(&self, index: usize) -> Age {
+        match (self.alive[index], self.counters[index]) {
+            (true, age) => Age::Alive(age),
+            (false, u16::MAX) => Age::Empty,
+            (false, time) => Age::Dead(time),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{pattern::Pattern, LifeLike};
+
+    #[test]
+    fn blinker_ages() {
+        let life = LifeLike::new("B3/S23").unwrap();
+        let blinker = Pattern::from_plaintext("OOO".lines()).unwrap();
+        let mut grid = Grid::from_pattern(&blinker, 2);
+        let mut ages = Ages::new(&grid);
+
+        // the blinker lies across row 2, from (2, 2) to (4, 2)
+        assert_eq!(ages.get((3, 2)), Age::Alive(0));
+        assert_eq!(ages.get((0, 0)), Age::Empty);
+
+        grid.step(&life);
+        ages.update(&grid);
+        assert_eq!(ages.get((3, 2)), Age::Alive(1));
+        assert_eq!(ages.get((3, 1)), Age::Alive(0));
+        assert_eq!(ages.get((2, 2)), Age::Dead(0));
+
+        grid.step(&life);
+        ages.update(&grid);
+        assert_eq!(ages.get((3, 2)), Age::Alive(2));
+        assert_eq!(ages.get((3, 1)), Age::Dead(0));
+        assert_eq!(ages.get((2, 2)), Age::Alive(0));
+        assert_eq!(
+            ages.iter().filter(|age| *age == Age::Empty).count(),
+            7 * 5 - 5
+        );
+    }
+
+    #[test]
+    fn edits_are_born_without_aging() {
+        let mut grid = Grid::new((4, 4));
+        let mut ages = Ages::new(&grid);
+
+        grid.set((1, 1), true);
+        ages.update(&grid);
+        assert_eq!(ages.get((1, 1)), Age::Alive(0));
+
+        // nothing moved forward, so nothing gets older
+        ages.update(&grid);
+        assert_eq!(ages.get((1, 1)), Age::Alive(0));
+    }
+}
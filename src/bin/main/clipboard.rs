@@ -0,0 +1,13 @@
+use macroquad::{miniquad::clipboard, prelude::get_internal_gl};
+
+/// The text on the system clipboard, if there is any.
+pub fn get() -> Option<String> {
+    let context = unsafe { get_internal_gl() }.quad_context;
+    clipboard::get(context)
+}
+
+/// Puts `text` on the system clipboard.
+pub fn set(text: &str) {
+    let context = unsafe { get_internal_gl() }.quad_context;
+    clipboard::set(context, text);
+}
@@ -0,0 +1,266 @@
+use life::{grid::Grid, pattern::Pattern};
+use macroquad::prelude::*;
+
+use crate::load;
+
+/// What a stroke does to the cells it passes over.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Brush {
+    /// Erase if the stroke starts on a live cell, otherwise draw.
+    Toggle,
+    Draw,
+    Erase,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Tool {
+    Freehand,
+    Line,
+    /// Draws the outline of a rectangle, or fills it while Shift is held.
+    Rectangle,
+    /// Drags out a rectangle of cells to copy or cut.
+    Select,
+}
+
+struct Stroke {
+    start: (usize, usize),
+    last: (usize, usize),
+    value: bool,
+}
+
+pub struct Editor {
+    pub tool: Tool,
+    pub brush: Brush,
+    stroke: Option<Stroke>,
+    /// The upper left corner and size of the selected cells.
+    selection: Option<((usize, usize), (usize, usize))>,
+    copied: Option<Pattern>,
+    /// A pasted pattern following the cursor until it is placed.
+    floating: Option<Pattern>,
+}
+
+impl Editor {
+    pub fn new() -> Self {
+        Editor {
+            tool: Tool::Freehand,
+            brush: Brush::Toggle,
+            stroke: None,
+            selection: None,
+            copied: None,
+            floating: None,
+        }
+    }
+
+    /// Switches tools and brushes, turns and flips a pasted pattern, and
+    /// cancels pasting or the selection. Returns whether the key was used.
+    pub fn handle_key(&mut self, key: KeyCode) -> bool {
+        match (key, &mut self.floating) {
+            (KeyCode::R, Some(floating)) => *floating = floating.rotate_clockwise(),
+            (KeyCode::F, Some(floating)) => *floating = floating.flip_horizontal(),
+            (KeyCode::Escape, Some(_)) => self.floating = None,
+            (KeyCode::Escape, None) if self.selection.is_some() => self.selection = None,
+            _ => return self.handle_tool_key(key),
+        }
+        true
+    }
+
+    fn handle_tool_key(&mut self, key: KeyCode) -> bool {
+        match key {
+            KeyCode::Key1 => self.tool = Tool::Freehand,
+            KeyCode::Key2 => self.tool = Tool::Line,
+            KeyCode::Key3 => self.tool = Tool::Rectangle,
+            KeyCode::Key4 => self.tool = Tool::Select,
+            KeyCode::T => self.brush = Brush::Toggle,
+            KeyCode::D => self.brush = Brush::Draw,
+            KeyCode::E => self.brush = Brush::Erase,
+            _ => return false,
+        }
+        true
+    }
+
+    /// Copies the selected cells. Returns them, or nothing if no cells are
+    /// selected.
+    pub fn copy(&mut self, grid: &Grid) -> Option<&Pattern> {
+        let (position, size) = self.selection?;
+        let pattern = Pattern::from_region(grid.cells(), grid.size(), position, size).ok()?;
+        self.copied = Some(pattern);
+        self.copied.as_ref()
+    }
+
+    /// Copies the selected cells, then clears them.
+    pub fn cut(&mut self, grid: &mut Grid) -> Option<&Pattern> {
+        let (position, size) = self.selection?;
+        self.copy(grid)?;
+        let (empty, grid_size) = (Pattern::new(size), grid.size());
+        empty.place(grid.cells_mut(), grid_size, position).ok()?;
+        self.copied.as_ref()
+    }
+
+    /// Starts pasting `pattern`, or the last copied cells if there is no
+    /// pattern. The paste follows the cursor until it is clicked into place.
+    pub fn paste(&mut self, pattern: Option<Pattern>) {
+        self.stroke = None;
+        self.floating = pattern.or_else(|| self.copied.clone());
+    }
+
+    /// Whether a stroke is being drawn.
+    pub fn stroking(&self) -> bool {
+        self.stroke.is_some()
+    }
+
+    /// Forgets the stroke in progress and the selection, which may no longer
+    /// fit after the world changes size.
+    pub fn cancel(&mut self) {
+        self.stroke = None;
+        self.selection = None;
+    }
+
+    /// The pattern being pasted, if any.
+    pub fn floating(&self) -> Option<&Pattern> {
+        self.floating.as_ref()
+    }
+
+    /// The selected cells, or the ones being selected, as the upper left
+    /// corner and size.
+    pub fn selection(&self) -> Option<((usize, usize), (usize, usize))> {
+        match &self.stroke {
+            Some(stroke) if self.tool == Tool::Select => Some(bounds(stroke.start, stroke.last)),
+            _ => self.selection,
+        }
+    }
+
+    /// Follows the mouse, editing `grid` as strokes are drawn. `cell` is the
+    /// cell under the cursor, if there is one. The right button always erases.
+    pub fn update(&mut self, grid: &mut Grid, cell: Option<(usize, usize)>) {
+        let left = is_mouse_button_pressed(MouseButton::Left);
+        let right = is_mouse_button_pressed(MouseButton::Right);
+        let down =
+            is_mouse_button_down(MouseButton::Left) || is_mouse_button_down(MouseButton::Right);
+
+        // a paste is placed with the left button and dropped with the right
+        if let Some(floating) = &self.floating {
+            match cell {
+                Some(cell) if left => {
+                    if let Err(e) = load::place_at(floating, grid, cell) {
+                        error!("could not paste! error:\n  {}", e);
+                    }
+                    self.floating = None;
+                }
+                _ if right => self.floating = None,
+                _ => {}
+            }
+            return;
+        }
+
+        match (&mut self.stroke, cell) {
+            (None, Some(cell)) if left || right => {
+                let value = match (right, self.brush) {
+                    (true, _) | (false, Brush::Erase) => false,
+                    (false, Brush::Draw) => true,
+                    (false, Brush::Toggle) => !grid.get(cell),
+                };
+
+                if self.tool == Tool::Freehand {
+                    grid.set(cell, value);
+                }
+                self.stroke = Some(Stroke {
+                    start: cell,
+                    last: cell,
+                    value,
+                });
+            }
+            (Some(stroke), Some(cell)) if down => {
+                // fill in the cells between mouse samples on fast drags
+                if self.tool == Tool::Freehand && cell != stroke.last {
+                    for point in line(stroke.last, cell) {
+                        grid.set(point, stroke.value);
+                    }
+                }
+                stroke.last = cell;
+            }
+            (Some(_), None) if down => {}
+            (Some(_), _) if self.tool == Tool::Select => {
+                self.selection = self.selection();
+                self.stroke = None;
+            }
+            (Some(_), _) => {
+                let cells = self.preview();
+                if let Some(stroke) = self.stroke.take() {
+                    for point in cells {
+                        grid.set(point, stroke.value);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The cells the current line or rectangle will change when the mouse is
+    /// released.
+    pub fn preview(&self) -> Vec<(usize, usize)> {
+        match &self.stroke {
+            Some(stroke) => match self.tool {
+                Tool::Freehand | Tool::Select => Vec::new(),
+                Tool::Line => line(stroke.start, stroke.last),
+                Tool::Rectangle => rectangle(
+                    stroke.start,
+                    stroke.last,
+                    is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift),
+                ),
+            },
+            None => Vec::new(),
+        }
+    }
+
+    /// Whether the current stroke draws (rather than erases) cells.
+    pub fn drawing(&self) -> bool {
+        self.stroke.as_ref().is_none_or(|stroke| stroke.value)
+    }
+}
+
+/// The cells on the straight line between two cells, inclusive.
+pub fn line(from: (usize, usize), to: (usize, usize)) -> Vec<(usize, usize)> {
+    let (mut x, mut y) = (from.0 as isize, from.1 as isize);
+    let (x1, y1) = (to.0 as isize, to.1 as isize);
+    let (dx, dy) = ((x1 - x).abs(), -(y1 - y).abs());
+    let (sx, sy) = ((x1 - x).signum(), (y1 - y).signum());
+    let mut error = dx + dy;
+
+    let mut cells = vec![(x as usize, y as usize)];
+    while (x, y) != (x1, y1) {
+        let doubled = 2 * error;
+        if doubled >= dy {
+            error += dy;
+            x += sx;
+        }
+        if doubled <= dx {
+            error += dx;
+            y += sy;
+        }
+        cells.push((x as usize, y as usize));
+    }
+
+    cells
+}
+
+/// The upper left corner and size of the rectangle with the given opposite
+/// corners, inclusive.
+pub fn bounds(from: (usize, usize), to: (usize, usize)) -> ((usize, usize), (usize, usize)) {
+    let corner = (from.0.min(to.0), from.1.min(to.1));
+    let size = (
+        from.0.max(to.0) - corner.0 + 1,
+        from.1.max(to.1) - corner.1 + 1,
+    );
+    (corner, size)
+}
+
+/// The cells of the rectangle with the given opposite corners, inclusive.
+pub fn rectangle(from: (usize, usize), to: (usize, usize), filled: bool) -> Vec<(usize, usize)> {
+    let (left, right) = (from.0.min(to.0), from.0.max(to.0));
+    let (top, bottom) = (from.1.min(to.1), from.1.max(to.1));
+
+    (top..=bottom)
+        .flat_map(|y| (left..=right).map(move |x| (x, y)))
+        .filter(|&(x, y)| filled || x == left || x == right || y == top || y == bottom)
+        .collect()
+}
@@ -0,0 +1,97 @@
+use life::{
+    grid::Grid,
+    image::{Gif, Style},
+};
+use macroquad::prelude::*;
+
+/// Saves `bytes` to a file called `name`. Natively the file is written to the
+/// working directory; on the web the browser downloads it.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save(name: &str, bytes: &[u8]) -> Result<(), &'static str> {
+    std::fs::write(name, bytes).map_err(|_| "could not write file")
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn save(name: &str, bytes: &[u8]) -> Result<(), &'static str> {
+    web::download_file(name, bytes);
+    Ok(())
+}
+
+#[cfg(target_arch = "wasm32")]
+mod web {
+    use wasm_bindgen::prelude::*;
+
+    #[wasm_bindgen]
+    extern "C" {
+        /// Defined in `index.html`.
+        pub fn download_file(name: &str, bytes: &[u8]);
+    }
+}
+
+/// The most frames a recording keeps, so that a fast simulation can't fill
+/// memory. Recording stops by itself once it's reached.
+const MAX_FRAMES: usize = 1000;
+
+/// Records generations to an animated GIF as they're run, and saves it when
+/// stopped.
+pub struct Recorder {
+    gif: Option<(String, Gif)>,
+}
+
+impl Recorder {
+    pub fn new() -> Self {
+        Recorder { gif: None }
+    }
+
+    pub fn recording(&self) -> bool {
+        self.gif.is_some()
+    }
+
+    /// Starts recording `size` cells from `position`, beginning with the
+    /// grid's current state. Each frame is shown for `delay` hundredths of a
+    /// second.
+    pub fn start(
+        &mut self,
+        grid: &Grid,
+        position: (usize, usize),
+        size: (usize, usize),
+        delay: u16,
+    ) -> Result<(), &'static str> {
+        let mut gif = Gif::new(position, size, &Style::default(), delay)?;
+        gif.add_frame(grid)?;
+        self.gif = Some((format!("life-{}.gif", grid.generation()), gif));
+        Ok(())
+    }
+
+    /// Adds the grid's current state. Recording stops when the region no
+    /// longer fits in the world or the recording is full.
+    pub fn record(&mut self, grid: &Grid) {
+        if let Some((_, gif)) = &mut self.gif {
+            if gif.add_frame(grid).is_err() || gif.frames() >= MAX_FRAMES {
+                self.stop();
+            }
+        }
+    }
+
+    /// Stops recording and saves what was recorded.
+    pub fn stop(&mut self) {
+        if let Some((name, gif)) = self.gif.take() {
+            match save(&name, &gif.finish()) {
+                Ok(()) => {
+                    info!("saved {}", name);
+                }
+                Err(e) => {
+                    error!("could not save {}! error:\n  {}", name, e);
+                }
+            }
+        }
+    }
+
+    /// Shows that a recording is running, in the top left corner.
+    pub fn draw(&self) {
+        if let Some((_, gif)) = &self.gif {
+            draw_circle(16., 16., 6., RED);
+            draw_text(&format!("REC {}", gif.frames()), 28., 22., 20., RED);
+        }
+    }
+}
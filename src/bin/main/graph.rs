@@ -0,0 +1,132 @@
+use std::collections::VecDeque;
+
+use life::grid::Grid;
+use macroquad::prelude::*;
+
+/// How many generations the graph remembers.
+const HISTORY: usize = 2000;
+const MIN_WIDTH: f32 = 160.;
+const MIN_HEIGHT: f32 = 80.;
+const PADDING: f32 = 8.;
+
+#[derive(Copy, Clone, Debug)]
+struct Sample {
+    generation: usize,
+    population: usize,
+    births: usize,
+    deaths: usize,
+}
+
+/// A scrolling chart of population, births and deaths over the last
+/// generations, in the lower left corner of the screen.
+pub struct Graph {
+    pub visible: bool,
+    size: Vec2,
+    samples: VecDeque<Sample>,
+}
+
+impl Graph {
+    pub fn new() -> Self {
+        Graph {
+            visible: false,
+            size: vec2(400., 160.),
+            samples: VecDeque::with_capacity(HISTORY),
+        }
+    }
+
+    /// Forgets every generation.
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    /// Notes the generation the grid just reached. Call after every step.
+    pub fn record(&mut self, grid: &Grid) {
+        // after stepping backwards, the graph picks up from the new generation
+        while matches!(self.samples.back(), Some(last) if last.generation >= grid.generation()) {
+            self.samples.pop_back();
+        }
+
+        if self.samples.len() == HISTORY {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(Sample {
+            generation: grid.generation(),
+            population: grid.population(),
+            births: grid.births(),
+            deaths: grid.deaths(),
+        });
+    }
+
+    /// Grows (or with a factor below one, shrinks) the graph on screen.
+    pub fn scale(&mut self, factor: f32) {
+        let min = vec2(MIN_WIDTH, MIN_HEIGHT);
+        let max = vec2(screen_width(), screen_height()) - vec2(2. * PADDING, 2. * PADDING);
+        self.size = (self.size * factor).max(min).min(max.max(min));
+    }
+
+    /// The remembered generations as CSV.
+    pub fn to_csv(&self) -> String {
+        let mut csv = "generation,population,births,deaths\n".to_owned();
+        for sample in self.samples.iter() {
+            csv.push_str(&format!(
+                "{},{},{},{}\n",
+                sample.generation, sample.population, sample.births, sample.deaths
+            ));
+        }
+        csv
+    }
+
+    pub fn draw(&self) {
+        if !self.visible {
+            return;
+        }
+
+        let corner = vec2(PADDING, screen_height() - self.size.y - PADDING);
+        draw_rectangle(
+            corner.x,
+            corner.y,
+            self.size.x,
+            self.size.y,
+            Color::new(0., 0., 0., 0.7),
+        );
+        draw_rectangle_lines(corner.x, corner.y, self.size.x, self.size.y, 1., DARKGRAY);
+
+        let max = self
+            .samples
+            .iter()
+            .map(|sample| sample.population.max(sample.births).max(sample.deaths))
+            .max()
+            .unwrap_or(0)
+            .max(1) as f32;
+
+        // the newest generation sits at the right edge and older ones scroll left
+        let plot = |value: fn(&Sample) -> usize, color: Color| {
+            let step = self.size.x / (HISTORY - 1) as f32;
+            let right = corner.x + self.size.x;
+            let point = |age: usize, sample: &Sample| {
+                vec2(
+                    right - age as f32 * step,
+                    corner.y + self.size.y * (1. - value(sample) as f32 / max),
+                )
+            };
+
+            let newest_first = self.samples.iter().rev().enumerate();
+            for ((age, newer), (_, older)) in newest_first.clone().zip(newest_first.skip(1)) {
+                let (a, b) = (point(age, newer), point(age + 1, older));
+                draw_line(a.x, a.y, b.x, b.y, 1., color);
+            }
+        };
+        plot(|sample| sample.deaths, RED);
+        plot(|sample| sample.births, GREEN);
+        plot(|sample| sample.population, WHITE);
+
+        let label = match self.samples.back() {
+            Some(last) => format!(
+                "population {}  births {}  deaths {}",
+                last.population, last.births, last.deaths
+            ),
+            None => "no generations yet".to_owned(),
+        };
+        draw_text(&label, corner.x + 4., corner.y + 14., 16., LIGHTGRAY);
+    }
+}
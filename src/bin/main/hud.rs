@@ -0,0 +1,83 @@
+use std::collections::VecDeque;
+
+use macroquad::prelude::*;
+
+const FONT_SIZE: f32 = 20.;
+const PADDING: f32 = 8.;
+/// How far back the step rate looks, in seconds.
+const RATE_WINDOW: f64 = 1.;
+
+/// An overlay in the corner of the screen describing the simulation.
+pub struct Hud {
+    pub visible: bool,
+    /// When each recent frame ended, and the generation it reached.
+    samples: VecDeque<(f64, usize)>,
+    /// Frame time in seconds, smoothed so the text stays readable.
+    frame_time: f32,
+}
+
+impl Hud {
+    pub fn new() -> Self {
+        Hud {
+            visible: true,
+            samples: VecDeque::new(),
+            frame_time: 0.,
+        }
+    }
+
+    /// Notes the generation reached this frame, for the step rate.
+    pub fn record(&mut self, generation: usize) {
+        let now = get_time();
+
+        // going back in time starts the measurement over
+        if matches!(self.samples.back(), Some(&(_, last)) if last > generation) {
+            self.samples.clear();
+        }
+        self.samples.push_back((now, generation));
+        while matches!(self.samples.front(), Some(&(time, _)) if now - time > RATE_WINDOW) {
+            self.samples.pop_front();
+        }
+
+        self.frame_time += (get_frame_time() - self.frame_time) * 0.1;
+    }
+
+    pub fn steps_per_second(&self) -> f32 {
+        match (self.samples.front(), self.samples.back()) {
+            (Some(&(start, first)), Some(&(end, last))) if end > start => {
+                ((last - first) as f64 / (end - start)) as f32
+            }
+            _ => 0.,
+        }
+    }
+
+    /// The smoothed frame time in milliseconds.
+    pub fn frame_time(&self) -> f32 {
+        self.frame_time * 1000.
+    }
+
+    /// Draws `lines` in the upper right corner, if the HUD is visible.
+    pub fn draw(&self, lines: &[String]) {
+        if !self.visible {
+            return;
+        }
+
+        let width = lines
+            .iter()
+            .map(|line| measure_text(line, None, FONT_SIZE as u16, 1.).width)
+            .fold(0., f32::max);
+        let height = lines.len() as f32 * FONT_SIZE;
+        let left = screen_width() - width - 3. * PADDING;
+
+        draw_rectangle(
+            left,
+            PADDING,
+            width + 2. * PADDING,
+            height + 2. * PADDING,
+            Color::new(0., 0., 0., 0.7),
+        );
+        for (row, line) in lines.iter().enumerate() {
+            let baseline = 2. * PADDING + (row as f32 + 0.75) * FONT_SIZE;
+            draw_text(line, left + PADDING, baseline, FONT_SIZE, WHITE);
+        }
+    }
+}
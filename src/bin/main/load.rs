@@ -0,0 +1,103 @@
+use life::{grid::Grid, pattern::Pattern};
+use macroquad::{
+    file::load_string,
+    prelude::*,
+    ui::{hash, root_ui, widgets},
+};
+
+// The files under `assets/pattern`, listed by the build script.
+include!(concat!(env!("OUT_DIR"), "/patterns.rs"));
+
+/// Loads one of the patterns listed in [`PATTERNS`].
+pub async fn load_asset(name: &str) -> Result<Pattern, &'static str> {
+    let text = load_string(&format!("pattern/{}", name))
+        .await
+        .map_err(|_| "could not load pattern asset")?;
+    Pattern::from_file(name, &text)
+}
+
+/// Files dropped on the window since the last call, as names and contents.
+///
+/// Only the web build receives dropped files: the native windowing backend
+/// does not report them, so natively patterns are opened from the command line
+/// or the browser instead.
+pub fn take_dropped() -> Vec<(String, String)> {
+    #[cfg(target_arch = "wasm32")]
+    return web::take_dropped();
+
+    #[cfg(not(target_arch = "wasm32"))]
+    Vec::new()
+}
+
+#[cfg(target_arch = "wasm32")]
+mod web {
+    use std::cell::RefCell;
+    use wasm_bindgen::prelude::*;
+
+    thread_local! {
+        static DROPPED: RefCell<Vec<(String, String)>> = RefCell::new(Vec::new());
+    }
+
+    /// Called by `index.html` with each file dropped on the canvas.
+    #[wasm_bindgen]
+    pub fn pattern_dropped(name: String, text: String) {
+        DROPPED.with(|dropped| dropped.borrow_mut().push((name, text)));
+    }
+
+    pub fn take_dropped() -> Vec<(String, String)> {
+        DROPPED.with(|dropped| dropped.borrow_mut().drain(..).collect())
+    }
+}
+
+/// Places `pattern` centered on `cell`, shifting it inwards where it would
+/// hang over the edge of the world.
+pub fn place_at(
+    pattern: &Pattern,
+    grid: &mut Grid,
+    cell: (usize, usize),
+) -> Result<(), &'static str> {
+    let size = grid.size();
+    let position = position_at(pattern.size(), cell, size)?;
+    pattern.place(grid.cells_mut(), size, position)
+}
+
+/// Where [`place_at`] puts the upper left corner of a pattern of `size`.
+pub fn position_at(
+    size: (usize, usize),
+    cell: (usize, usize),
+    grid_size: (usize, usize),
+) -> Result<(usize, usize), &'static str> {
+    if size.0 > grid_size.0 || size.1 > grid_size.1 {
+        return Err("pattern is larger than the world");
+    }
+
+    Ok((
+        cell.0.saturating_sub(size.0 / 2).min(grid_size.0 - size.0),
+        cell.1.saturating_sub(size.1 / 2).min(grid_size.1 - size.1),
+    ))
+}
+
+/// Shows the patterns under `assets/pattern` and returns the one that was
+/// picked, if any.
+pub fn browser() -> Option<&'static str> {
+    let mut picked = None;
+
+    widgets::Window::new(hash!(), vec2(20., 20.), vec2(300., 400.))
+        .movable(true)
+        .label("Patterns")
+        .ui(&mut root_ui(), |ui| {
+            for &name in PATTERNS {
+                if ui.button(None, display_name(name).as_str()) {
+                    picked = Some(name);
+                }
+            }
+        });
+
+    picked
+}
+
+/// Turns a file name like `gosper_glider_gun.cells` into `gosper glider gun`.
+fn display_name(name: &str) -> String {
+    let stem = name.rsplit_once('.').map_or(name, |(stem, _)| stem);
+    stem.replace('_', " ")
+}
@@ -0,0 +1,653 @@
+#![allow(dead_code)]
+
+use core::f32;
+
+mod clipboard;
+mod edit;
+mod export;
+mod graph;
+mod hud;
+mod load;
+mod options;
+mod render;
+mod rules;
+mod settings;
+mod share;
+mod skin;
+mod storage;
+mod text;
+mod view;
+
+use edit::Editor;
+use export::Recorder;
+use graph::Graph;
+use hud::Hud;
+use life::{
+    age::Ages,
+    grid::Grid,
+    history::{self, History},
+    image::{self, Style},
+    pattern::Pattern,
+    snapshot::Snapshot,
+    speed::Speed,
+    LifeLike,
+};
+use options::Options;
+use render::{Benchmark, Renderer};
+use settings::{Change, Current, Settings};
+use skin::Skins;
+use text::TextStamp;
+use view::View;
+
+use ::rand::{rngs::StdRng, Rng, SeedableRng};
+use bitvec::prelude::*;
+use macroquad::{file::set_pc_assets_folder, prelude::*};
+
+const HEIGHT: usize = 512;
+const WIDTH: usize = 1024;
+const INITIAL_RULE: &str = "B3/S23";
+const INITIAL_PATTERN: &str = "gosper_glider_gun.cells";
+const BENCHMARK_FRAMES: usize = 300;
+/// The longest a frame may spend stepping, in seconds, so that drawing and
+/// input stay responsive when the target rate can't be reached.
+const FRAME_BUDGET: f64 = 0.012;
+
+fn window_conf() -> Conf {
+    let options = Options::get();
+    Conf {
+        window_title: "chips".to_owned(),
+        window_width: options.window.0 as i32,
+        window_height: options.window.1 as i32,
+        window_resizable: true,
+        high_dpi: false,
+        ..Default::default()
+    }
+}
+
+/// What happens to the world when the window changes size.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Resize {
+    /// Keep the world as it is and scale the view to match the window.
+    Scale,
+    /// Grow or shrink the world with the window, keeping the cells centered.
+    World,
+}
+
+impl Resize {
+    fn toggled(self) -> Self {
+        match self {
+            Resize::Scale => Resize::World,
+            Resize::World => Resize::Scale,
+        }
+    }
+}
+
+struct World {
+    state: State,
+    machine: LifeLike,
+    speed: Speed,
+}
+
+impl World {
+    fn new(initial_rule: &str) -> Self {
+        World {
+            state: State::Normal,
+            machine: LifeLike::new(initial_rule).unwrap(),
+            speed: Speed::default(),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum State {
+    /// The main state of the simulation. The machine should simulate
+    Normal,
+    Paused,
+    Settings,
+    /// Picking a pattern to place from the assets.
+    Browser,
+    /// Writing text to paste into the world.
+    Text,
+}
+
+impl State {
+    fn next(&self, input: KeyCode, touches: Vec<Touch>) -> State {
+        match (self, input, !touches.is_empty()) {
+            (State::Normal | State::Paused, KeyCode::Escape, _) => State::Settings,
+            (State::Normal, KeyCode::Space, _) | (State::Normal, _, true) => State::Paused,
+            (State::Normal | State::Paused, KeyCode::O, _) => State::Browser,
+            (State::Normal | State::Paused, KeyCode::A, _) => State::Text,
+            (State::Settings, KeyCode::Escape, _) => State::Normal,
+            (State::Browser, KeyCode::Escape | KeyCode::O, _) => State::Normal,
+            (State::Text, KeyCode::Escape, _) => State::Normal,
+            (State::Paused, KeyCode::Space, _) | (State::Paused, _, true) => State::Normal,
+            _ => *self,
+        }
+    }
+}
+
+#[macroquad::main(window_conf)]
+async fn main() {
+    #[cfg(not(target_arch = "wasm32"))]
+    set_pc_assets_folder("assets");
+
+    #[cfg(target_arch = "wasm32")]
+    set_pc_assets_folder("");
+
+    let options = Options::get();
+    let mut rng = match options.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+
+    let (grid_width, grid_height) = options.world_size();
+    let mut grid = Grid::new((grid_width, grid_height));
+
+    // a seed without a pattern asks for a reproducible soup
+    let pattern = match (&options.pattern, options.seed) {
+        (Some(pattern), _) => Some(pattern.clone()),
+        (None, Some(_)) => None,
+        (None, None) => Some(load::load_asset(INITIAL_PATTERN).await.unwrap()),
+    };
+
+    match pattern {
+        Some(pattern) => {
+            if let Err(e) = pattern
+                .calc_midpoint_placement((grid_width, grid_height))
+                .and_then(|position| {
+                    pattern.place(grid.cells_mut(), (grid_width, grid_height), position)
+                })
+            {
+                error!("could not place pattern! error:\n  {}", e);
+            }
+        }
+        None => fill_random(grid.cells_mut(), options.density, &mut rng),
+    }
+
+    let mut world = World::new(&options.rule);
+    world.speed = Speed::new(options.speed);
+    if options.restore {
+        match read_snapshot(&options.snapshot) {
+            Ok(snapshot) => {
+                grid = snapshot.grid;
+                world.machine = snapshot.rule;
+                world.speed = Speed::new(snapshot.speed);
+            }
+            Err(e) => {
+                error!("could not restore {}! error:\n  {}", options.snapshot, e);
+            }
+        }
+    }
+    if options.paused {
+        world.state = State::Paused;
+    }
+    let mut editor = Editor::new();
+    let mut view = View::new(
+        grid.size(),
+        options.zoom.unwrap_or(options.resolution as f32),
+    );
+    if let Some((x, y)) = options.center {
+        view.look_at(vec2(x, y));
+    }
+    let mut renderer = Renderer::new(grid.size());
+    renderer.colors = options.colors;
+    let mut ages = options.colors.uses_ages().then(|| Ages::new(&grid));
+    let mut benchmark: Option<Benchmark> = None;
+    let mut hud = Hud::new();
+    let mut graph = Graph::new();
+    graph.record(&grid);
+    let mut history = History::new(&grid, history::Limits::default());
+    let mut recorder = Recorder::new();
+    let mut settings = Settings::new(&world.machine, grid.size(), options.density);
+    let mut skins = Skins::new(options.theme);
+    let mut text_stamp = TextStamp::new();
+    let mut resize = options.resize;
+    let mut window = vec2(screen_width(), screen_height());
+
+    loop {
+        clear_background(BLACK); // clear all previous drawings
+
+        // follow the window when it changes size (but not when it's minimized)
+        let resized = vec2(screen_width(), screen_height());
+        if resized != window && resized.x >= 1. && resized.y >= 1. {
+            match resize {
+                Resize::Scale => view.rescale(window),
+                Resize::World => {
+                    let scale = |cells: usize, old: f32, new: f32| {
+                        ((cells as f32 * new / old).round() as usize).max(1)
+                    };
+                    let size = (
+                        scale(grid.size().0, window.x, resized.x),
+                        scale(grid.size().1, window.y, resized.y),
+                    );
+                    match render::check_size(size) {
+                        Ok(()) => {
+                            view.shift(grid.resize(size));
+                            editor.cancel();
+                        }
+                        // a world too large to draw keeps its size, and the view scales
+                        Err(_) => view.rescale(window),
+                    }
+                }
+            }
+            window = resized;
+        }
+
+        // render the fresh information
+        let cursor = view.cell_at(mouse_position().into(), grid.size());
+        renderer.draw(grid.cells(), grid.size(), ages.as_ref(), &view);
+        render_preview(&editor, &view, cursor, grid.size());
+
+        if let Some(benchmark) = &mut benchmark {
+            benchmark.record();
+            benchmark.draw();
+        }
+
+        // process possible state changes
+        let editing = matches!(world.state, State::Normal | State::Paused);
+        // keys typed into a text box aren't shortcuts, but Escape still closes it
+        let typing = matches!(world.state, State::Settings | State::Text);
+        let command = [
+            KeyCode::LeftControl,
+            KeyCode::RightControl,
+            KeyCode::LeftSuper,
+            KeyCode::RightSuper,
+        ]
+        .iter()
+        .any(|&key| is_key_down(key));
+        match get_last_key_pressed().filter(|&key| !typing || key == KeyCode::Escape) {
+            Some(KeyCode::C) if editing && command => {
+                if let Some(copied) = editor.copy(&grid) {
+                    clipboard::set(&copied.to_rle(Some(&world.machine)));
+                }
+            }
+            Some(KeyCode::X) if editing && command => {
+                if let Some(cut) = editor.cut(&mut grid) {
+                    clipboard::set(&cut.to_rle(Some(&world.machine)));
+                }
+            }
+            Some(KeyCode::S) if editing && command => {
+                let snapshot = Snapshot {
+                    rule: world.machine.clone(),
+                    grid: grid.clone(),
+                    speed: world.speed.exponent(),
+                };
+                match storage::write(&options.snapshot, &snapshot.write()) {
+                    Ok(()) => {
+                        info!("saved {}", options.snapshot);
+                    }
+                    Err(e) => {
+                        error!("could not save {}! error:\n  {}", options.snapshot, e);
+                    }
+                }
+            }
+            Some(KeyCode::O) if editing && command => match read_snapshot(&options.snapshot) {
+                Ok(snapshot) => {
+                    grid = snapshot.grid;
+                    world.machine = snapshot.rule;
+                    world.speed = Speed::new(snapshot.speed);
+                    settings.set_rule(&world.machine);
+                    editor.cancel();
+                    history.clear(&grid);
+                    graph.clear();
+                    graph.record(&grid);
+                    if let Some(ages) = &mut ages {
+                        *ages = Ages::new(&grid);
+                    }
+                    info!("restored {}", options.snapshot);
+                }
+                Err(e) => {
+                    error!("could not restore {}! error:\n  {}", options.snapshot, e);
+                }
+            },
+            Some(KeyCode::L) if command => {
+                clipboard::set(&share::link(
+                    &world.machine,
+                    &grid,
+                    world.speed.exponent(),
+                    world.state == State::Paused,
+                    &view,
+                ));
+                info!("copied a link to this world");
+            }
+            Some(KeyCode::Z) if editing && command => {
+                history.undo(&mut grid);
+            }
+            Some(KeyCode::V) if editing && command => {
+                // fall back to the last copy when the clipboard holds no pattern
+                let pasted = clipboard::get()
+                    .and_then(|text| Pattern::from_file("", &text).ok())
+                    .filter(|pattern| pattern.population() > 0);
+                editor.paste(pasted);
+            }
+            Some(KeyCode::E) if command => {
+                let name = format!("population-{}.csv", grid.generation());
+                match export::save(&name, graph.to_csv().as_bytes()) {
+                    Ok(()) => {
+                        info!("saved {}", name);
+                    }
+                    Err(e) => {
+                        error!("could not save {}! error:\n  {}", name, e);
+                    }
+                }
+            }
+            Some(KeyCode::P) => {
+                let (position, size) = picture_region(&editor, &view, grid.size());
+                let name = format!("life-{}.png", grid.generation());
+                match image::png(&grid, position, size, &Style::default())
+                    .and_then(|bytes| export::save(&name, &bytes))
+                {
+                    Ok(()) => {
+                        info!("saved {}", name);
+                    }
+                    Err(e) => {
+                        error!("could not save {}! error:\n  {}", name, e);
+                    }
+                }
+            }
+            Some(KeyCode::M) if recorder.recording() => recorder.stop(),
+            Some(KeyCode::M) => {
+                let (position, size) = picture_region(&editor, &view, grid.size());
+                // frames last as long as generations do, as far as GIFs allow
+                let delay = (100. / world.speed.rate())
+                    .round()
+                    .clamp(2., u16::MAX as f64);
+                match recorder.start(&grid, position, size, delay as u16) {
+                    Ok(()) => {
+                        info!("started recording");
+                    }
+                    Err(e) => {
+                        error!("could not start recording! error:\n  {}", e);
+                    }
+                }
+            }
+            Some(KeyCode::N) => fill_random(grid.cells_mut(), settings.density(), &mut rng),
+            Some(KeyCode::G) => graph.visible = !graph.visible,
+            Some(KeyCode::C) if !command => {
+                renderer.colors = renderer.colors.next();
+                ages = renderer.colors.uses_ages().then(|| Ages::new(&grid));
+            }
+            Some(KeyCode::Period) => world.speed.faster(),
+            Some(KeyCode::Comma) => world.speed.slower(),
+            Some(KeyCode::Key0) => world.speed.set_exponent(0),
+            Some(KeyCode::LeftBracket) if graph.visible => graph.scale(1. / 1.25),
+            Some(KeyCode::RightBracket) if graph.visible => graph.scale(1.25),
+            Some(KeyCode::H) => hud.visible = !hud.visible,
+            Some(KeyCode::W) => {
+                resize = resize.toggled();
+                info!("resizing the window now resizes: {:?}", resize);
+            }
+            Some(KeyCode::B) => match benchmark {
+                Some(ref running) if running.running() => {}
+                Some(_) => benchmark = None,
+                None => {
+                    // time frames on a dense soup, where drawing costs the most
+                    fill_random(grid.cells_mut(), 0.5, &mut rng);
+                    world.state = State::Normal;
+                    benchmark = Some(Benchmark::new(BENCHMARK_FRAMES));
+                }
+            },
+            Some(key) if editing && editor.handle_key(key) => {}
+            Some(key) if editing && view.handle_key(key, grid.size()) => {}
+            Some(input) => world.state = world.state.next(input, touches()),
+            None => {}
+        }
+
+        // drawing and moving around work whether or not the simulation is running
+        if editing {
+            view.update();
+            if touches().len() < 2 {
+                editor.update(&mut grid, cursor);
+            }
+        }
+
+        // files dropped on the window land under the cursor
+        for (name, text) in load::take_dropped() {
+            let cell = view
+                .cell_at(mouse_position().into(), grid.size())
+                .unwrap_or_else(|| view.center_cell(grid.size()));
+            match Pattern::from_file(&name, &text).and_then(|p| load::place_at(&p, &mut grid, cell))
+            {
+                Ok(()) => {
+                    info!("placed {}", name);
+                }
+                Err(e) => {
+                    error!("could not place {}! error:\n  {}", name, e);
+                }
+            }
+        }
+
+        // edits are remembered once each stroke is finished
+        if !editor.stroking() {
+            history.record(&grid);
+        }
+
+        // proceed according to current state
+        match world.state {
+            State::Normal => {
+                let machine = &world.machine;
+                let start = get_time();
+                let out_of_time = || get_time() - start > FRAME_BUDGET;
+                world.speed.run(get_frame_time() as f64, out_of_time, || {
+                    step(
+                        &mut grid,
+                        machine,
+                        &mut graph,
+                        &mut history,
+                        &mut ages,
+                        &mut recorder,
+                    )
+                });
+            }
+            State::Paused => {
+                // if paused, don't do anything unless an arrow key was pressed
+                // (Shift with the arrow keys pans the view instead)
+                let shift = is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift);
+                if is_key_pressed(KeyCode::Right) && !shift {
+                    step(
+                        &mut grid,
+                        &world.machine,
+                        &mut graph,
+                        &mut history,
+                        &mut ages,
+                        &mut recorder,
+                    );
+                } else if is_key_pressed(KeyCode::Left) && !shift {
+                    history.undo(&mut grid);
+                }
+            }
+            State::Settings => {
+                let changes = settings.ui(&Current {
+                    rule: &world.machine,
+                    speed: &world.speed,
+                    size: grid.size(),
+                    resize,
+                    colors: renderer.colors,
+                    theme: skins.theme(),
+                });
+                for change in changes {
+                    match change {
+                        Change::Rule(rule) => {
+                            info!("changed rule to {}", rule);
+                            world.machine = rule;
+                        }
+                        Change::Speed(exponent) => world.speed.set_exponent(exponent),
+                        Change::Size(size) => {
+                            view.shift(grid.resize(size));
+                            editor.cancel();
+                            info!("resized the world to {}x{}", size.0, size.1);
+                        }
+                        Change::Resize(mode) => resize = mode,
+                        Change::Colors(colors) => {
+                            renderer.colors = colors;
+                            ages = colors.uses_ages().then(|| Ages::new(&grid));
+                        }
+                        Change::Theme(theme) => skins.set(theme),
+                        Change::Soup => fill_random(grid.cells_mut(), settings.density(), &mut rng),
+                    }
+                }
+            }
+            State::Browser => {
+                if let Some(name) = load::browser() {
+                    let cell = view.center_cell(grid.size());
+                    match load::load_asset(name)
+                        .await
+                        .and_then(|p| load::place_at(&p, &mut grid, cell))
+                    {
+                        Ok(()) => {
+                            info!("placed {}", name);
+                            world.state = State::Paused;
+                        }
+                        Err(e) => {
+                            error!("could not place {}! error:\n  {}", name, e);
+                        }
+                    }
+                }
+            }
+            State::Text => {
+                // the text follows the cursor like any other paste
+                if let Some(pattern) = text_stamp.ui() {
+                    editor.paste(Some(pattern));
+                    world.state = State::Paused;
+                }
+            }
+        }
+
+        // edits and undone generations are picked up as births and deaths
+        if let Some(ages) = &mut ages {
+            ages.update(&grid);
+        }
+
+        graph.draw();
+        recorder.draw();
+        hud.record(grid.generation());
+        hud.draw(&[
+            format!("generation {}", grid.generation()),
+            format!("population {}", grid.population()),
+            format!("changed {}", grid.changed()),
+            format!("rule {}", world.machine),
+            format!("colors {}", renderer.colors),
+            match world.state {
+                State::Normal => format!("{}", world.speed),
+                _ => format!("{} (paused)", world.speed),
+            },
+            format!(
+                "{:.1} steps/s, {:.1} ms/frame",
+                hud.steps_per_second(),
+                hud.frame_time()
+            ),
+            match cursor {
+                Some((x, y)) => format!("cell {}, {}", x, y),
+                None => "cell -".to_owned(),
+            },
+        ]);
+
+        next_frame().await
+    }
+}
+
+/// Advances the world one generation, charting it, remembering it for
+/// stepping back, aging its cells if they're being colored by age, and
+/// adding it to the recording if one is running.
+fn step(
+    grid: &mut Grid,
+    machine: &LifeLike,
+    graph: &mut Graph,
+    history: &mut History,
+    ages: &mut Option<Ages>,
+    recorder: &mut Recorder,
+) {
+    grid.step(machine);
+    graph.record(grid);
+    history.record(grid);
+    if let Some(ages) = ages {
+        ages.update(grid);
+    }
+    recorder.record(grid);
+}
+
+/// Reads the snapshot saved under `name`, if its world can be drawn.
+fn read_snapshot(name: &str) -> Result<Snapshot, &'static str> {
+    let snapshot = Snapshot::read(&storage::read(name)?)?;
+    render::check_size(snapshot.grid.size())?;
+    Ok(snapshot)
+}
+
+/// The cells pictures are taken of: the selection if there is one, or else
+/// every cell on the screen.
+fn picture_region(
+    editor: &Editor,
+    view: &View,
+    grid_size: (usize, usize),
+) -> ((usize, usize), (usize, usize)) {
+    editor.selection().unwrap_or_else(|| {
+        let ((left, top), (right, bottom)) = view.visible(grid_size);
+        (
+            (left, top),
+            (right.saturating_sub(left), bottom.saturating_sub(top)),
+        )
+    })
+}
+
+/// Makes each cell alive with a chance of `density`.
+fn fill_random<O: BitOrder, T: BitStore>(
+    slice: &mut BitSlice<O, T>,
+    density: f64,
+    rng: &mut impl Rng,
+) {
+    slice.iter_mut().for_each(|i| i.set(rng.gen_bool(density)));
+}
+
+/// Highlights the cells a pending line or rectangle will change, outlines the
+/// selection, and shows where a paste will land.
+fn render_preview(
+    editor: &Editor,
+    view: &View,
+    cursor: Option<(usize, usize)>,
+    grid_size: (usize, usize),
+) {
+    let color = if editor.drawing() {
+        Color::new(1., 1., 1., 0.5)
+    } else {
+        Color::new(1., 0., 0., 0.5)
+    };
+
+    let zoom = view.zoom();
+    for (x, y) in editor.preview() {
+        let upper_left = view.world_to_screen(vec2(x as f32, y as f32));
+        draw_rectangle(upper_left.x, upper_left.y, zoom, zoom, color);
+    }
+
+    if let Some((position, size)) = editor.selection() {
+        render_outline(view, position, size, YELLOW);
+    }
+
+    let floating = editor.floating().and_then(|pattern| {
+        let position = load::position_at(pattern.size(), cursor?, grid_size).ok()?;
+        Some((pattern, position))
+    });
+    if let Some((pattern, position)) = floating {
+        let color = Color::new(0., 1., 0., 0.6);
+        for y in 0..pattern.size().1 {
+            for x in (0..pattern.size().0).filter(|&x| pattern.get((x, y))) {
+                let cell = vec2((position.0 + x) as f32, (position.1 + y) as f32);
+                let upper_left = view.world_to_screen(cell);
+                draw_rectangle(upper_left.x, upper_left.y, zoom, zoom, color);
+            }
+        }
+        render_outline(view, position, pattern.size(), GREEN);
+    }
+}
+
+fn render_outline(view: &View, position: (usize, usize), size: (usize, usize), color: Color) {
+    let upper_left = view.world_to_screen(vec2(position.0 as f32, position.1 as f32));
+    let zoom = view.zoom();
+    draw_rectangle_lines(
+        upper_left.x,
+        upper_left.y,
+        size.0 as f32 * zoom,
+        size.1 as f32 * zoom,
+        2.,
+        color,
+    );
+}
@@ -0,0 +1,430 @@
+use std::sync::OnceLock;
+
+use life::{cli, pattern::Pattern};
+
+#[cfg(target_arch = "wasm32")]
+use macroquad::prelude::error;
+
+#[cfg(target_arch = "wasm32")]
+use crate::share;
+use crate::{
+    render::{self, Colors},
+    skin::Theme,
+    Resize,
+};
+
+const USAGE: &str = "usage: main [OPTIONS] [PATTERN]
+
+Options:
+    --pattern PATH      Start from a plaintext (.cells) or RLE (.rle) pattern
+    --rle TEXT          Start from a pattern given as RLE text
+    --apgcode CODE      Start from a pattern given as an apgcode, like xq4_153
+    --image PATH        Start from a PNG picture, with dark pixels alive
+    --image-scale S     Cells per pixel of the picture (default: 1)
+    --dither MODE       Turn shades into cells by threshold (threshold), error
+                        diffusion (floyd-steinberg), or a Bayer matrix
+                        (ordered); default: threshold
+    --threshold N       Gray level, from 0 to 255, that dark pixels are below
+                        (default: 128)
+    --invert            Make light pixels alive instead of dark ones
+    --text TEXT         Start from text in the bundled font, where \\n starts
+                        a new line
+    --text-height N     Height of each line of text in cells, from 1 to 1024
+                        (default: 16)
+    --rule RULE         Rule to simulate, like B3/S23
+    --size WxH          World size in cells, at most 16384 on a side and
+                        16777216 in all (default: window size / resolution)
+    --resolution N      Pixels per cell when starting (default: 2)
+    --window WxH        Window size in pixels (default: 1024x512)
+    --zoom Z            Pixels per cell of the view (default: the resolution)
+    --center X,Y        Cell in the middle of the view (default: the middle of
+                        the world)
+    --speed N           Run at 60 * 2^N generations per second, for N from -6
+                        to 12 (default: 0)
+    --resize MODE       When the window changes size, scale the view (scale) or
+                        resize the world with it (world); default: scale
+    --colors SCHEME     Color cells plainly (plain), by age (age), with fading
+                        trails (trails), or by births (births); default: plain
+    --theme THEME       Draw windows dark (dark) or light (light); default: dark
+    --density P         Chance of each cell being alive in random soups, from
+                        0 to 1 (default: 0.5)
+    --paused            Start with the simulation paused
+    --seed N            Seed for random soups; without a pattern, start from one
+    --snapshot PATH     File that Ctrl+S saves to and Ctrl+O restores from
+                        (default: life.snapshot)
+    --restore           Start from the saved snapshot
+    --help              Show this message";
+
+pub struct Options {
+    pub rule: String,
+    pub pattern: Option<Pattern>,
+    pub size: Option<(usize, usize)>,
+    pub resolution: usize,
+    pub window: (usize, usize),
+    pub zoom: Option<f32>,
+    pub center: Option<(f32, f32)>,
+    /// The exponent of the speed, see [`life::speed::Speed`].
+    pub speed: i32,
+    pub resize: Resize,
+    pub colors: Colors,
+    pub theme: Theme,
+    /// The share of live cells in random soups.
+    pub density: f64,
+    pub paused: bool,
+    pub seed: Option<u64>,
+    pub snapshot: String,
+    pub restore: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            rule: crate::INITIAL_RULE.to_owned(),
+            pattern: None,
+            size: None,
+            resolution: 2,
+            window: (crate::WIDTH, crate::HEIGHT),
+            zoom: None,
+            center: None,
+            speed: 0,
+            resize: Resize::Scale,
+            colors: Colors::Plain,
+            theme: Theme::Dark,
+            density: 0.5,
+            paused: false,
+            seed: None,
+            snapshot: "life.snapshot".to_owned(),
+            restore: false,
+        }
+    }
+}
+
+impl Options {
+    /// Reads the options from command line arguments, not including the
+    /// program name.
+    pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = Options::default();
+        // the picture and text are read once all of their options are known
+        let mut picture = cli::Picture::default();
+        let mut text = cli::Text::default();
+
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("missing value for {}", arg))
+            };
+
+            if picture.parse_arg(&arg, &mut value)? || text.parse_arg(&arg, &mut value)? {
+                continue;
+            }
+            match arg.as_str() {
+                "--pattern" => options.pattern = Some(cli::read_pattern(&value()?)?),
+                "--rle" => {
+                    let rle = value()?;
+                    options.pattern = Some(
+                        Pattern::from_rle(&rle).map_err(|e| format!("invalid RLE: {}", e))?,
+                    );
+                }
+                "--apgcode" => {
+                    let code = value()?;
+                    options.pattern = Some(
+                        Pattern::from_apgcode(&code)
+                            .map_err(|e| format!("invalid apgcode {}: {}", code, e))?,
+                    );
+                }
+                "--rule" => {
+                    options.rule = cli::parse_rule(&value()?)?.to_string();
+                }
+                "--size" => {
+                    let size = value()?;
+                    options.size = Some(parse_world_size(&size).map_err(|e| {
+                        format!("invalid value for --size: {}, got {}", e, size)
+                    })?);
+                }
+                "--resolution" => {
+                    options.resolution = parse_positive("--resolution", &value()?)?;
+                }
+                "--window" => options.window = parse_dimensions("--window", &value()?)?,
+                "--zoom" => {
+                    let zoom = value()?;
+                    options.zoom = Some(
+                        zoom.parse()
+                            .ok()
+                            .filter(|&zoom: &f32| zoom > 0. && zoom.is_finite())
+                            .ok_or_else(|| {
+                                format!(
+                                    "invalid value for --zoom: expected a positive number, got {}",
+                                    zoom
+                                )
+                            })?,
+                    );
+                }
+                "--center" => {
+                    let center = value()?;
+                    let invalid =
+                        || format!("invalid value for --center: expected X,Y, got {}", center);
+                    let (x, y) = center.split_once(',').ok_or_else(invalid)?;
+                    options.center = Some((
+                        x.trim().parse().map_err(|_| invalid())?,
+                        y.trim().parse().map_err(|_| invalid())?,
+                    ));
+                }
+                "--speed" => options.speed = cli::parse_speed(&value()?)?,
+                "--resize" => {
+                    options.resize = match value()?.as_str() {
+                        "scale" => Resize::Scale,
+                        "world" => Resize::World,
+                        other => {
+                            return Err(format!(
+                                "invalid value for --resize: expected scale or world, got {}",
+                                other
+                            ))
+                        }
+                    }
+                }
+                "--colors" => {
+                    options.colors = match value()?.as_str() {
+                        "plain" => Colors::Plain,
+                        "age" => Colors::Age,
+                        "trails" => Colors::Trails,
+                        "births" => Colors::Births,
+                        other => {
+                            return Err(format!(
+                                "invalid value for --colors: expected plain, age, trails, or births, got {}",
+                                other
+                            ))
+                        }
+                    }
+                }
+                "--theme" => {
+                    options.theme = match value()?.as_str() {
+                        "dark" => Theme::Dark,
+                        "light" => Theme::Light,
+                        other => {
+                            return Err(format!(
+                                "invalid value for --theme: expected dark or light, got {}",
+                                other
+                            ))
+                        }
+                    }
+                }
+                "--density" => {
+                    let density = value()?;
+                    options.density = density
+                        .parse()
+                        .ok()
+                        .filter(|p| (0. ..=1.).contains(p))
+                        .ok_or_else(|| {
+                            format!(
+                                "invalid value for --density: expected 0 to 1, got {}",
+                                density
+                            )
+                        })?;
+                }
+                "--paused" => options.paused = true,
+                "--snapshot" => options.snapshot = value()?,
+                "--restore" => options.restore = true,
+                "--seed" => {
+                    let seed = value()?;
+                    options.seed = Some(
+                        seed.parse()
+                            .map_err(|_| format!("invalid value for --seed: {}", seed))?,
+                    );
+                }
+                flag if flag.starts_with('-') => {
+                    return Err(format!("unknown option {}\n\n{}", flag, USAGE))
+                }
+                path => options.pattern = Some(cli::read_pattern(path)?),
+            }
+        }
+
+        // a world that fills a large window at a fine resolution can be too big
+        let (width, height) = options.world_size();
+        render::check_size((width, height)).map_err(|e| {
+            format!(
+                "invalid world size {}x{} from --window and --resolution: {}",
+                width, height, e
+            )
+        })?;
+
+        if let Some(pattern) = picture.read()? {
+            options.pattern = Some(pattern);
+        }
+        if let Some(pattern) = text.pattern()? {
+            options.pattern = Some(pattern);
+        }
+
+        Ok(options)
+    }
+
+    /// The options the program was started with, or on the web, the options
+    /// in the page's address. Invalid options print the problem and exit
+    /// before any window opens.
+    pub fn get() -> &'static Options {
+        static OPTIONS: OnceLock<Options> = OnceLock::new();
+
+        OPTIONS.get_or_init(|| {
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                let args: Vec<String> = std::env::args().skip(1).collect();
+                if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+                    println!("{}", USAGE);
+                    std::process::exit(0);
+                }
+
+                Options::parse(args.into_iter()).unwrap_or_else(|message| {
+                    eprintln!("{}", message);
+                    std::process::exit(2);
+                })
+            }
+
+            // the page can't be left, so bad options are only reported
+            #[cfg(target_arch = "wasm32")]
+            Options::parse(share::page_args().into_iter()).unwrap_or_else(|message| {
+                error!("ignoring the options in the address! error:\n  {}", message);
+                Options::default()
+            })
+        })
+    }
+
+    /// The world size, which by default fills the window at the starting
+    /// resolution.
+    pub fn world_size(&self) -> (usize, usize) {
+        self.size.unwrap_or((
+            (self.window.0 / self.resolution).max(1),
+            (self.window.1 / self.resolution).max(1),
+        ))
+    }
+}
+
+fn parse_positive(name: &str, value: &str) -> Result<usize, String> {
+    match value.parse() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(format!(
+            "invalid value for {}: expected a positive number, got {}",
+            name, value
+        )),
+    }
+}
+
+/// Reads a world size written as `WxH`, checking that it can be drawn.
+pub fn parse_world_size(text: &str) -> Result<(usize, usize), &'static str> {
+    let (width, height) = text.trim().split_once('x').ok_or("expected WxH")?;
+    let side = |side: &str| side.trim().parse().map_err(|_| "expected numbers");
+    let size = (side(width)?, side(height)?);
+    render::check_size(size)?;
+    Ok(size)
+}
+
+fn parse_dimensions(name: &str, value: &str) -> Result<(usize, usize), String> {
+    let (width, height) = value
+        .split_once('x')
+        .ok_or_else(|| format!("invalid value for {}: expected WxH, got {}", name, value))?;
+    Ok((parse_positive(name, width)?, parse_positive(name, height)?))
+}
+
+#[cfg(test)]
+mod test {
+    use life::text::Font;
+
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        Options::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    fn error(args: &[&str]) -> String {
+        match parse(args) {
+            Ok(_) => panic!("{:?} should be rejected", args),
+            Err(message) => message,
+        }
+    }
+
+    #[test]
+    fn parses_options() {
+        let options = parse(&[]).unwrap();
+        assert_eq!(options.world_size(), (512, 256));
+        assert_eq!(options.pattern, None);
+
+        let options = parse(&[
+            "--rle",
+            "x = 3, y = 3\nbo$2bo$3o!",
+            "--rule",
+            "b36/s23",
+            "--size",
+            "64x32",
+            "--zoom",
+            "4",
+            "--center",
+            "10, -2.5",
+            "--speed",
+            "-3",
+            "--resize",
+            "world",
+            "--colors",
+            "trails",
+            "--theme",
+            "light",
+            "--density",
+            "0.25",
+            "--paused",
+            "--seed",
+            "7",
+            "--snapshot",
+            "saved.snapshot",
+            "--restore",
+        ])
+        .unwrap();
+        assert_eq!(options.pattern.as_ref().unwrap().population(), 5);
+        assert_eq!(options.rule, "B36/S23");
+        assert_eq!(options.world_size(), (64, 32));
+        assert_eq!(options.zoom, Some(4.));
+        assert_eq!(options.center, Some((10., -2.5)));
+        assert_eq!(options.speed, -3);
+        assert_eq!(options.resize, Resize::World);
+        assert_eq!(options.colors, Colors::Trails);
+        assert_eq!(options.theme, Theme::Light);
+        assert_eq!(options.density, 0.25);
+        assert!(options.paused && options.restore);
+        assert_eq!(options.seed, Some(7));
+        assert_eq!(options.snapshot, "saved.snapshot");
+
+        let options = parse(&["--window", "800x600", "--resolution", "4"]).unwrap();
+        assert_eq!(options.world_size(), (200, 150));
+
+        // text and pictures replace any other pattern, whatever the order
+        let options = parse(&["--text", "I", "--apgcode", "xq4_153"]).unwrap();
+        assert_eq!(options.pattern, Font::rubik().pattern("I", 16).ok());
+    }
+
+    #[test]
+    fn rejects_options() {
+        assert!(error(&["--size", "16384x1025"]).starts_with("invalid value for --size"));
+        assert!(error(&["--size", "16385x1"]).contains("16384"));
+        assert!(error(&["--size", "70000x2"]).contains("16384"));
+        assert!(error(&["--size", "8000x8000"]).contains("16777216"));
+        assert!(error(&["--size", "0x5"]).contains("empty"));
+        assert!(error(&["--size", "64"]).contains("expected WxH"));
+        assert!(error(&["--window", "20000x20000"]).contains("--window and --resolution"));
+        assert!(parse(&["--window", "20000x20000", "--resolution", "8"]).is_ok());
+        assert!(parse(&["--window", "20000x20000", "--size", "64x64"]).is_ok());
+        assert!(error(&["--resolution", "0"]).contains("positive"));
+
+        assert!(error(&["--frobnicate"]).starts_with("unknown option --frobnicate"));
+        assert_eq!(error(&["--size"]), "missing value for --size");
+        assert_eq!(error(&["--image"]), "missing value for --image");
+        assert_eq!(error(&["--text-height"]), "missing value for --text-height");
+
+        assert!(error(&["--rule", "B9"]).contains("B9"));
+        assert!(error(&["--speed", "13"]).contains("--speed"));
+        assert!(error(&["--zoom", "0"]).contains("--zoom"));
+        assert!(error(&["--center", "1"]).contains("--center"));
+        assert!(error(&["--resize", "stretch"]).contains("--resize"));
+        assert!(error(&["--colors", "rainbow"]).contains("--colors"));
+        assert!(error(&["--theme", "blue"]).contains("--theme"));
+        assert!(error(&["--density", "1.5"]).contains("--density"));
+        assert!(error(&["--seed", "-1"]).contains("--seed"));
+        assert!(error(&["--rle", "bo$2bo$3o!"]).contains("invalid RLE"));
+        assert!(error(&["no/such/pattern.rle"]).contains("could not read"));
+    }
+}
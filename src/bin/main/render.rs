@@ -0,0 +1,390 @@
+use std::fmt;
+
+use bitvec::prelude::*;
+use life::age::{Age, Ages};
+use macroquad::prelude::*;
+
+use crate::view::View;
+
+/// How many generations a dead cell's trail takes to fade out.
+const TRAIL: u16 = 32;
+/// Cells at least this old get the last color of the age gradient.
+const OLD: u16 = 1000;
+/// The widest or tallest world that can be drawn. The world is a single
+/// texture, and GPUs commonly allow textures up to this size.
+pub const MAX_SIDE: usize = 16384;
+/// The most cells a world can have. Each takes four bytes of the image.
+pub const MAX_CELLS: usize = 1 << 24;
+
+/// Checks that a world of `size` cells can be drawn.
+pub fn check_size(size: (usize, usize)) -> Result<(), &'static str> {
+    if size.0 == 0 || size.1 == 0 {
+        Err("world is empty")
+    } else if size.0 > MAX_SIDE || size.1 > MAX_SIDE {
+        Err("world is wider or taller than 16384 cells")
+    } else if size.0 * size.1 > MAX_CELLS {
+        Err("world has more than 16777216 cells")
+    } else {
+        Ok(())
+    }
+}
+
+/// How live and dead cells are colored.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Colors {
+    /// Live cells are white.
+    Plain,
+    /// Live cells go from yellow when born, through red, to blue as they age.
+    Age,
+    /// Live cells are white, and cells that died recently fade out in red.
+    Trails,
+    /// Births are green, survivals blue, and cells that just died dark red.
+    Births,
+}
+
+pub const COLORS: [Colors; 4] = [Colors::Plain, Colors::Age, Colors::Trails, Colors::Births];
+
+impl Colors {
+    pub fn next(self) -> Self {
+        match self {
+            Colors::Plain => Colors::Age,
+            Colors::Age => Colors::Trails,
+            Colors::Trails => Colors::Births,
+            Colors::Births => Colors::Plain,
+        }
+    }
+
+    /// Whether the scheme needs cell ages, which are only tracked while one
+    /// does.
+    pub fn uses_ages(self) -> bool {
+        self != Colors::Plain
+    }
+
+    fn color(self, age: Age) -> [u8; 3] {
+        match (self, age) {
+            (Colors::Age, Age::Alive(age)) => {
+                let t = ((1. + age.min(OLD) as f32).ln() / (1. + OLD as f32).ln()).min(1.);
+                if t < 0.5 {
+                    lerp([255, 240, 80], [230, 40, 40], t * 2.)
+                } else {
+                    lerp([230, 40, 40], [60, 80, 255], t * 2. - 1.)
+                }
+            }
+            (Colors::Trails, Age::Alive(_)) => [255, 255, 255],
+            (Colors::Trails, Age::Dead(time)) if time < TRAIL => {
+                lerp([200, 50, 30], [0, 0, 0], time as f32 / TRAIL as f32)
+            }
+            (Colors::Births, Age::Alive(0)) => [40, 230, 40],
+            (Colors::Births, Age::Alive(_)) => [90, 140, 255],
+            (Colors::Births, Age::Dead(0)) => [140, 20, 20],
+            (Colors::Plain, Age::Alive(_)) => [255, 255, 255],
+            _ => [0, 0, 0],
+        }
+    }
+}
+
+impl fmt::Display for Colors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Colors::Plain => "plain",
+            Colors::Age => "age",
+            Colors::Trails => "trails",
+            Colors::Births => "births",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+fn lerp(from: [u8; 3], to: [u8; 3], t: f32) -> [u8; 3] {
+    let channel = |i: usize| (from[i] as f32 + (to[i] as f32 - from[i] as f32) * t) as u8;
+    [channel(0), channel(1), channel(2)]
+}
+
+/// Draws the world as a single texture with one pixel per cell.
+///
+/// The texture is kept in sync by comparing the world against the last
+/// uploaded generation, so only the rows containing changed cells are sent to
+/// the GPU each frame. When zoomed out far enough that several cells share a
+/// pixel, a second, smaller texture holding the density of each block of
+/// cells is drawn instead.
+///
+/// Color schemes other than [`Colors::Plain`] can change any cell's color in
+/// any generation, so they repaint and upload the whole world every frame.
+pub struct Renderer {
+    pub colors: Colors,
+    size: (usize, usize),
+    /// Whether the image was last painted in a scheme other than plain.
+    colored: bool,
+    uploaded: BitVec<Lsb0, usize>,
+    image: Image,
+    texture: Texture2D,
+    density: Option<(Image, Texture2D)>,
+}
+
+impl Renderer {
+    /// Makes a renderer for a world of `size` cells, which must pass
+    /// [`check_size`].
+    pub fn new(size: (usize, usize)) -> Self {
+        if let Err(e) = check_size(size) {
+            panic!("can't draw a {}x{} world: {}", size.0, size.1, e);
+        }
+        let image = Image::gen_image_color(size.0 as u16, size.1 as u16, BLACK);
+        let texture = Texture2D::from_image(&image);
+        texture.set_filter(FilterMode::Nearest);
+
+        Renderer {
+            colors: Colors::Plain,
+            size,
+            colored: false,
+            uploaded: BitVec::repeat(false, size.0 * size.1),
+            image,
+            texture,
+            density: None,
+        }
+    }
+
+    pub fn draw(
+        &mut self,
+        cells: &BitSlice<Lsb0, usize>,
+        size: (usize, usize),
+        ages: Option<&Ages>,
+        view: &View,
+    ) {
+        if size != self.size {
+            self.texture.delete();
+            if let Some((_, texture)) = &self.density {
+                texture.delete();
+            }
+            let colors = self.colors;
+            *self = Renderer::new(size);
+            self.colors = colors;
+        }
+
+        let zoom = view.zoom();
+        let corner = view.world_to_screen(vec2(0., 0.));
+
+        if zoom >= 1. {
+            match ages {
+                Some(ages) if self.colors.uses_ages() => self.paint(ages),
+                _ => self.sync(cells),
+            }
+            draw_texture_ex(
+                self.texture,
+                corner.x,
+                corner.y,
+                WHITE,
+                DrawTextureParams {
+                    dest_size: Some(vec2(size.0 as f32 * zoom, size.1 as f32 * zoom)),
+                    ..Default::default()
+                },
+            );
+        } else {
+            self.draw_density(cells, view);
+        }
+
+        draw_rectangle_lines(
+            corner.x,
+            corner.y,
+            size.0 as f32 * zoom,
+            size.1 as f32 * zoom,
+            1.,
+            DARKGRAY,
+        );
+    }
+
+    /// Colors every cell by its age and uploads the whole image.
+    fn paint(&mut self, ages: &Ages) {
+        for (index, age) in ages.iter().enumerate() {
+            let color = self.colors.color(age);
+            self.image.bytes[(index * 4)..(index * 4 + 3)].copy_from_slice(&color);
+        }
+        self.texture.update(&self.image);
+        self.colored = true;
+    }
+
+    /// Copies cells that changed since the last upload into the image, and
+    /// uploads the band of rows that contains them.
+    fn sync(&mut self, cells: &BitSlice<Lsb0, usize>) {
+        if self.colored {
+            // pretend every cell changed, so the colors are painted over
+            for (uploaded, alive) in self.uploaded.iter_mut().zip(cells.iter().by_val()) {
+                uploaded.set(!alive);
+            }
+            self.colored = false;
+        }
+
+        let mut rows: Option<(usize, usize)> = None;
+        let width = self.size.0;
+
+        let words = cells
+            .as_raw_slice()
+            .iter()
+            .zip(self.uploaded.as_raw_slice());
+        for (word, (&fresh, &old)) in words.enumerate() {
+            let mut changed = fresh ^ old;
+            while changed != 0 {
+                let bit = changed.trailing_zeros() as usize;
+                changed &= changed - 1;
+
+                let index = word * usize::BITS as usize + bit;
+                if index >= cells.len() {
+                    break;
+                }
+
+                let color = if cells[index] { 255 } else { 0 };
+                let pixel = &mut self.image.bytes[(index * 4)..(index * 4 + 3)];
+                pixel.iter_mut().for_each(|channel| *channel = color);
+
+                let row = index / width;
+                rows =
+                    Some(rows.map_or((row, row), |(first, last)| (first.min(row), last.max(row))));
+            }
+        }
+
+        if let Some((first, last)) = rows {
+            self.uploaded.copy_from_bitslice(cells);
+
+            let bytes = &self.image.bytes[(first * width * 4)..((last + 1) * width * 4)];
+            let context = unsafe { get_internal_gl() }.quad_context;
+            self.texture
+                .raw_miniquad_texture_handle()
+                .update_texture_part(
+                    context,
+                    0,
+                    first as i32,
+                    width as i32,
+                    (last - first + 1) as i32,
+                    bytes,
+                );
+        }
+    }
+
+    /// Shades each block of cells that shares a screen pixel by how many of
+    /// them are alive.
+    fn draw_density(&mut self, cells: &BitSlice<Lsb0, usize>, view: &View) {
+        let ((left, top), (right, bottom)) = view.visible(self.size);
+        if left >= right || top >= bottom {
+            return;
+        }
+
+        let step = (1. / view.zoom()).ceil() as usize;
+        let first = (left / step, top / step);
+        let columns = (right - 1) / step - first.0 + 1;
+        let rows = (bottom - 1) / step - first.1 + 1;
+
+        let mut counts = vec![0usize; columns * rows];
+        for y in top..bottom {
+            let start = left + (y * self.size.0);
+            for x in cells[start..(start + right - left)]
+                .iter_ones()
+                .map(|x| x + left)
+            {
+                counts[(x / step - first.0) + ((y / step - first.1) * columns)] += 1;
+            }
+        }
+
+        let (image, texture) = match &mut self.density {
+            Some((image, texture))
+                if image.width as usize == columns && image.height as usize == rows =>
+            {
+                (image, *texture)
+            }
+            density => {
+                if let Some((_, texture)) = density {
+                    texture.delete();
+                }
+                let image = Image::gen_image_color(columns as u16, rows as u16, BLACK);
+                let texture = Texture2D::from_image(&image);
+                texture.set_filter(FilterMode::Nearest);
+                *density = Some((image, texture));
+                let (image, texture) = density.as_mut().unwrap();
+                (image, *texture)
+            }
+        };
+
+        let full = (step * step) as f32;
+        for (index, &count) in counts.iter().enumerate() {
+            let shade = (count as f32 / full * 255.) as u8;
+            let pixel = &mut image.bytes[(index * 4)..(index * 4 + 3)];
+            pixel.iter_mut().for_each(|channel| *channel = shade);
+        }
+        texture.update(image);
+
+        let upper_left =
+            view.world_to_screen(vec2((first.0 * step) as f32, (first.1 * step) as f32));
+        let block = step as f32 * view.zoom();
+        draw_texture_ex(
+            texture,
+            upper_left.x,
+            upper_left.y,
+            WHITE,
+            DrawTextureParams {
+                dest_size: Some(vec2(columns as f32 * block, rows as f32 * block)),
+                ..Default::default()
+            },
+        );
+    }
+}
+
+/// Measures frame times over a fixed number of frames.
+pub struct Benchmark {
+    frames: usize,
+    times: Vec<f32>,
+    report: Option<String>,
+}
+
+impl Benchmark {
+    pub fn new(frames: usize) -> Self {
+        Benchmark {
+            frames,
+            times: Vec::with_capacity(frames),
+            report: None,
+        }
+    }
+
+    pub fn running(&self) -> bool {
+        self.report.is_none()
+    }
+
+    /// Records the time the last frame took, and summarizes the run once
+    /// enough frames have been seen.
+    pub fn record(&mut self) {
+        if !self.running() {
+            return;
+        }
+
+        self.times.push(get_frame_time() * 1000.);
+        if self.times.len() == self.frames {
+            let mut sorted = self.times.clone();
+            sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+            let mean = sorted.iter().sum::<f32>() / sorted.len() as f32;
+            let percentile = sorted[(sorted.len() * 95 / 100).min(sorted.len() - 1)];
+            let report = format!(
+                "{} frames: mean {:.2} ms, min {:.2} ms, p95 {:.2} ms, max {:.2} ms",
+                sorted.len(),
+                mean,
+                sorted[0],
+                percentile,
+                sorted[sorted.len() - 1]
+            );
+            info!("benchmark: {}", report);
+            self.report = Some(report);
+        }
+    }
+
+    pub fn draw(&self) {
+        let text = match &self.report {
+            Some(report) => report.clone(),
+            None => format!("benchmarking... {}/{}", self.times.len(), self.frames),
+        };
+        draw_rectangle(
+            0.,
+            screen_height() - 28.,
+            screen_width(),
+            28.,
+            Color::new(0., 0., 0., 0.7),
+        );
+        draw_text(&text, 8., screen_height() - 8., 24., YELLOW);
+    }
+}
@@ -0,0 +1,138 @@
+use life::LifeLike;
+use macroquad::{
+    prelude::*,
+    ui::{hash, widgets, Ui},
+};
+
+/// Well-known rules, by name.
+pub const PRESETS: &[(&str, &str)] = &[
+    ("Life", "B3/S23"),
+    ("HighLife", "B36/S23"),
+    ("Day & Night", "B3678/S34678"),
+    ("Seeds", "B2/S"),
+    ("Replicator", "B1357/S1357"),
+    ("Maze", "B3/S12345"),
+    ("Mazectric", "B3/S1234"),
+    ("Life without Death", "B3/S012345678"),
+    ("2x2", "B36/S125"),
+    ("Diamoeba", "B35678/S5678"),
+    ("Morley", "B368/S245"),
+    ("Anneal", "B4678/S35678"),
+];
+
+/// The rule section of the settings window: a text box, a grid of
+/// checkboxes with one per neighbor count for births (B) and survivals (S),
+/// and a list of presets, all kept in step with each other.
+///
+/// The checkboxes and presets switch rules straight away. Text is checked as
+/// it's typed, and only used once the button is pressed, so that half-typed
+/// rules don't run.
+pub struct RuleEditor {
+    text: String,
+}
+
+impl RuleEditor {
+    pub fn new(rule: &LifeLike) -> Self {
+        RuleEditor {
+            text: rule.to_string(),
+        }
+    }
+
+    /// Replaces the text with a rule that was changed some other way.
+    pub fn set(&mut self, rule: &LifeLike) {
+        self.text = rule.to_string();
+    }
+
+    /// Shows the editor for the `current` rule, returning the rule to switch
+    /// to if one was picked.
+    pub fn ui(&mut self, ui: &mut Ui, current: &LifeLike) -> Option<LifeLike> {
+        let mut picked = None;
+        let typed = LifeLike::new(self.text.trim());
+
+        // the first entry stands for any rule that isn't a preset
+        let names: Vec<&str> = std::iter::once("Custom")
+            .chain(PRESETS.iter().map(|&(name, _)| name))
+            .collect();
+        let shown = typed
+            .as_ref()
+            .ok()
+            .and_then(preset)
+            .map_or(0, |index| index + 1);
+        let mut selected = shown;
+        ui.combo_box(hash!(), "Preset", &names, &mut selected);
+        if selected != shown && selected > 0 {
+            picked = LifeLike::new(PRESETS[selected - 1].1).ok();
+        }
+
+        ui.input_text(hash!(), "Rule", &mut self.text);
+        ui.label(
+            None,
+            &match &typed {
+                Ok(rule) if rule == current => format!("running {}", rule),
+                Ok(rule) => format!("press Update rule to run {}", rule),
+                Err(e) => format!("invalid rule: {}", e),
+            },
+        );
+        if ui.button(None, "Update rule") {
+            picked = typed.as_ref().ok().cloned();
+        }
+
+        // the boxes show the typed rule while it's valid
+        let shown = typed.as_ref().unwrap_or(current);
+        let mut boxes = [[false; 9]; 2];
+        for (status, row) in boxes.iter_mut().enumerate() {
+            for (neighbors, checked) in row.iter_mut().enumerate() {
+                *checked = shown.simulate(status == 1, neighbors);
+            }
+        }
+        let before = boxes;
+
+        widgets::Group::new(hash!(), vec2(400., 22.)).ui(ui, |ui| {
+            widgets::Group::new(hash!(), vec2(24., 22.)).ui(ui, |_| {});
+            for neighbors in 0..9 {
+                widgets::Group::new(hash!(("digit", neighbors)), vec2(34., 22.)).ui(ui, |ui| {
+                    ui.label(None, &format!(" {}", neighbors));
+                });
+            }
+        });
+        for (status, row) in boxes.iter_mut().enumerate() {
+            widgets::Group::new(hash!(("row", status)), vec2(400., 26.)).ui(ui, |ui| {
+                widgets::Group::new(hash!(("name", status)), vec2(24., 26.)).ui(ui, |ui| {
+                    ui.label(None, ["B", "S"][status]);
+                });
+                for (neighbors, checked) in row.iter_mut().enumerate() {
+                    widgets::Group::new(hash!(("box", status, neighbors)), vec2(34., 26.))
+                        .ui(ui, |ui| {
+                            ui.checkbox(hash!(("check", status, neighbors)), "", checked)
+                        });
+                }
+            });
+        }
+        if boxes != before {
+            picked = LifeLike::new(&rule_string(&boxes)).ok();
+        }
+
+        if let Some(rule) = &picked {
+            self.set(rule);
+        }
+        picked
+    }
+}
+
+/// The index of the preset with this rule, if there is one.
+fn preset(rule: &LifeLike) -> Option<usize> {
+    PRESETS
+        .iter()
+        .position(|&(_, preset)| LifeLike::new(preset).as_ref() == Ok(rule))
+}
+
+/// Writes the rule with these births (first) and survivals (second).
+fn rule_string(boxes: &[[bool; 9]; 2]) -> String {
+    let digits = |row: &[bool; 9]| {
+        (0..9)
+            .filter(|&neighbors| row[neighbors])
+            .map(|neighbors| char::from(b'0' + neighbors as u8))
+            .collect::<String>()
+    };
+    format!("B{}/S{}", digits(&boxes[0]), digits(&boxes[1]))
+}
@@ -0,0 +1,200 @@
+use life::{
+    speed::{Speed, MAX_EXPONENT, MIN_EXPONENT},
+    LifeLike,
+};
+use macroquad::{
+    prelude::*,
+    ui::{hash, root_ui, widgets},
+};
+
+use crate::{
+    options::parse_world_size,
+    render::{Colors, COLORS},
+    rules::RuleEditor,
+    skin::{Theme, THEMES},
+    Resize,
+};
+
+const RESIZES: [(Resize, &str); 2] = [
+    (Resize::Scale, "Scale the view"),
+    (Resize::World, "Resize the world"),
+];
+
+/// A setting picked in the settings window.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Change {
+    Rule(LifeLike),
+    /// The exponent of the speed.
+    Speed(i32),
+    Size((usize, usize)),
+    Resize(Resize),
+    Colors(Colors),
+    Theme(Theme),
+    /// Fill the world with a new random soup.
+    Soup,
+}
+
+/// What the world is running with, to show in the settings window.
+pub struct Current<'a> {
+    pub rule: &'a LifeLike,
+    pub speed: &'a Speed,
+    pub size: (usize, usize),
+    pub resize: Resize,
+    pub colors: Colors,
+    pub theme: Theme,
+}
+
+/// The settings window, in sections for the rule, speed, topology, colors,
+/// and soup generation.
+///
+/// The window only shows the settings, and reports the ones that were
+/// changed, so that nothing is overwritten while it's merely open.
+pub struct Settings {
+    rules: RuleEditor,
+    size_text: String,
+    /// The world size the text was last filled in from.
+    size_shown: (usize, usize),
+    density: f32,
+}
+
+impl Settings {
+    pub fn new(rule: &LifeLike, size: (usize, usize), density: f64) -> Self {
+        Settings {
+            rules: RuleEditor::new(rule),
+            size_text: size_string(size),
+            size_shown: size,
+            density: density as f32,
+        }
+    }
+
+    /// Replaces the rule's text with a rule that was changed some other way.
+    pub fn set_rule(&mut self, rule: &LifeLike) {
+        self.rules.set(rule);
+    }
+
+    /// The share of live cells in random soups.
+    pub fn density(&self) -> f64 {
+        self.density as f64
+    }
+
+    /// Shows the window, returning the settings that were changed.
+    pub fn ui(&mut self, current: &Current) -> Vec<Change> {
+        let mut changes = Vec::new();
+
+        // the size follows the world when it changes some other way
+        if current.size != self.size_shown {
+            self.size_text = size_string(current.size);
+            self.size_shown = current.size;
+        }
+
+        widgets::Window::new(hash!(), vec2(20., 20.), vec2(460., 560.))
+            .movable(true)
+            .label("Settings")
+            .ui(&mut root_ui(), |ui| {
+                widgets::TreeNode::new(hash!(), "Rule")
+                    .init_unfolded()
+                    .ui(ui, |ui| {
+                        if let Some(rule) = self.rules.ui(ui, current.rule) {
+                            changes.push(Change::Rule(rule));
+                        }
+                    });
+                ui.separator();
+
+                widgets::TreeNode::new(hash!(), "Speed")
+                    .init_unfolded()
+                    .ui(ui, |ui| {
+                        let mut exponent = current.speed.exponent() as f32;
+                        ui.slider(
+                            hash!(),
+                            "Speed",
+                            MIN_EXPONENT as f32..MAX_EXPONENT as f32,
+                            &mut exponent,
+                        );
+                        if exponent.round() as i32 != current.speed.exponent() {
+                            changes.push(Change::Speed(exponent.round() as i32));
+                        }
+                        ui.label(None, &current.speed.to_string());
+                    });
+                ui.separator();
+
+                widgets::TreeNode::new(hash!(), "Topology")
+                    .init_unfolded()
+                    .ui(ui, |ui| {
+                        ui.label(None, "The world wraps around at its edges, like a torus.");
+                        ui.input_text(hash!(), "Size", &mut self.size_text);
+                        let typed = parse_world_size(&self.size_text);
+                        ui.label(
+                            None,
+                            &match typed {
+                                Ok(size) if size == current.size => {
+                                    format!("world is {}", size_string(size))
+                                }
+                                Ok(size) => {
+                                    format!("press Resize world to make it {}", size_string(size))
+                                }
+                                Err(e) => format!("invalid size: {}", e),
+                            },
+                        );
+                        if ui.button(None, "Resize world") {
+                            if let Ok(size) = typed {
+                                changes.push(Change::Size(size));
+                            }
+                        }
+
+                        let names: Vec<&str> = RESIZES.iter().map(|&(_, name)| name).collect();
+                        let shown = RESIZES
+                            .iter()
+                            .position(|&(resize, _)| resize == current.resize)
+                            .unwrap();
+                        let mut selected = shown;
+                        ui.combo_box(hash!(), "Window resizing", &names, &mut selected);
+                        if selected != shown {
+                            changes.push(Change::Resize(RESIZES[selected].0));
+                        }
+                    });
+                ui.separator();
+
+                widgets::TreeNode::new(hash!(), "Colors")
+                    .init_unfolded()
+                    .ui(ui, |ui| {
+                        let names: Vec<String> = COLORS.iter().map(Colors::to_string).collect();
+                        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+                        let shown = COLORS.iter().position(|&c| c == current.colors).unwrap();
+                        let mut selected = shown;
+                        ui.combo_box(hash!(), "Cells", &names, &mut selected);
+                        if selected != shown {
+                            changes.push(Change::Colors(COLORS[selected]));
+                        }
+
+                        let names: Vec<String> = THEMES.iter().map(Theme::to_string).collect();
+                        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+                        let shown = THEMES.iter().position(|&t| t == current.theme).unwrap();
+                        let mut selected = shown;
+                        ui.combo_box(hash!(), "Windows", &names, &mut selected);
+                        if selected != shown {
+                            changes.push(Change::Theme(THEMES[selected]));
+                        }
+                    });
+                ui.separator();
+
+                widgets::TreeNode::new(hash!(), "Soup")
+                    .init_unfolded()
+                    .ui(ui, |ui| {
+                        ui.slider(hash!(), "Density", 0. ..1., &mut self.density);
+                        ui.label(
+                            None,
+                            &format!("{:.0}% of cells start alive", self.density * 100.),
+                        );
+                        if ui.button(None, "New soup") {
+                            changes.push(Change::Soup);
+                        }
+                    });
+            });
+
+        changes
+    }
+}
+
+fn size_string(size: (usize, usize)) -> String {
+    format!("{}x{}", size.0, size.1)
+}
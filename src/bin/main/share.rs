@@ -0,0 +1,138 @@
+//! Links that start the viewer in a given state.
+//!
+//! On the web, options are read from the page's query and fragment as
+//! `key=value` pairs named after the command line options, like
+//! `?rule=B36/S23&apgcode=xq4_153&speed=2`. Flags without a value, like
+//! `paused`, are written on their own.
+
+use life::{grid::Grid, pattern::Pattern, LifeLike};
+
+use crate::view::View;
+
+/// Where links point natively, since there's no page to link back to.
+const SITE: &str = "https://life.cgm616.me/";
+
+/// Turns the query and fragment of a URL into command line arguments.
+pub fn args(params: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    for pair in params.split(['?', '#', '&']) {
+        match pair.split_once('=') {
+            Some((key, value)) => {
+                args.push(format!("--{}", decode(key)));
+                args.push(decode(value));
+            }
+            None if !pair.is_empty() => args.push(format!("--{}", decode(pair))),
+            None => {}
+        }
+    }
+    args
+}
+
+/// A link to the page that starts with this world, rule, speed and view.
+///
+/// The whole world is written as RLE in the fragment rather than the query,
+/// since fragments aren't sent to the server and so aren't cut short by its
+/// limits on URL length.
+pub fn link(rule: &LifeLike, grid: &Grid, speed: i32, paused: bool, view: &View) -> String {
+    let world = Pattern::from_region(grid.cells(), grid.size(), (0, 0), grid.size())
+        .expect("the world lies inside itself");
+    let center = view.center();
+
+    let mut link = format!(
+        "{}#rule={}&speed={}&size={}x{}&zoom={}&center={},{}",
+        base(),
+        encode(&rule.to_string()),
+        speed,
+        grid.size().0,
+        grid.size().1,
+        view.zoom(),
+        center.x,
+        center.y,
+    );
+    if paused {
+        link.push_str("&paused");
+    }
+    link.push_str("&rle=");
+    link.push_str(&encode(&world.to_rle(None)));
+    link
+}
+
+/// Escapes everything but letters, digits, and the few symbols that RLE and
+/// rules use and that URLs allow as they are.
+fn encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            b'/' | b'$' | b'!' | b',' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// Undoes percent escapes, and `+` for spaces as forms write them. Broken
+/// escapes are kept as they are.
+fn decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let escaped = bytes
+            .get(index + 1..index + 3)
+            .filter(|_| bytes[index] == b'%')
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[index], escaped) {
+            (_, Some(byte)) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            (b'+', None) => {
+                decoded.push(b' ');
+                index += 1;
+            }
+            (byte, None) => {
+                decoded.push(byte);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn base() -> String {
+    SITE.to_owned()
+}
+
+/// The page's address, without its query or fragment.
+#[cfg(target_arch = "wasm32")]
+fn base() -> String {
+    let url = web::page_url();
+    let end = url.find(['?', '#']).unwrap_or(url.len());
+    url[..end].to_owned()
+}
+
+/// The arguments given in the page's address.
+#[cfg(target_arch = "wasm32")]
+pub fn page_args() -> Vec<String> {
+    let url = web::page_url();
+    match url.find(['?', '#']) {
+        Some(start) => args(&url[start..]),
+        None => Vec::new(),
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod web {
+    use wasm_bindgen::prelude::*;
+
+    #[wasm_bindgen]
+    extern "C" {
+        /// Defined in `index.html`.
+        pub fn page_url() -> String;
+    }
+}
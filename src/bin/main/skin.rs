@@ -0,0 +1,179 @@
+use std::fmt;
+
+use life::text::RUBIK;
+use macroquad::{
+    prelude::*,
+    ui::{root_ui, Skin, StyleBuilder},
+};
+
+/// The size of all text in windows. Section headings are laid out 14 pixels
+/// tall, so anything larger overlaps the first row below them.
+const FONT_SIZE: u16 = 14;
+
+/// The color scheme of the windows. Both use the bundled Rubik font.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Theme {
+    Dark,
+    Light,
+}
+
+pub const THEMES: [Theme; 2] = [Theme::Dark, Theme::Light];
+
+/// The colors a theme is made from.
+struct Palette {
+    window: Color,
+    border: Color,
+    text: Color,
+    control: Color,
+    hovered: Color,
+    clicked: Color,
+    /// Checked boxes, slider handles and the open combo box entry.
+    accent: Color,
+    /// The background of text boxes.
+    field: Color,
+    selection: Color,
+}
+
+impl Theme {
+    fn palette(self) -> Palette {
+        match self {
+            Theme::Dark => Palette {
+                window: Color::from_rgba(28, 30, 36, 240),
+                border: Color::from_rgba(70, 74, 86, 255),
+                text: Color::from_rgba(225, 228, 235, 255),
+                control: Color::from_rgba(52, 56, 66, 255),
+                hovered: Color::from_rgba(66, 71, 84, 255),
+                clicked: Color::from_rgba(84, 90, 106, 255),
+                accent: Color::from_rgba(86, 156, 240, 255),
+                field: Color::from_rgba(16, 17, 21, 255),
+                selection: Color::from_rgba(60, 96, 150, 255),
+            },
+            Theme::Light => Palette {
+                window: Color::from_rgba(244, 244, 246, 240),
+                border: Color::from_rgba(160, 162, 170, 255),
+                text: Color::from_rgba(24, 24, 28, 255),
+                control: Color::from_rgba(218, 220, 226, 255),
+                hovered: Color::from_rgba(204, 207, 215, 255),
+                clicked: Color::from_rgba(186, 190, 200, 255),
+                accent: Color::from_rgba(40, 110, 210, 255),
+                field: Color::from_rgba(255, 255, 255, 255),
+                selection: Color::from_rgba(170, 200, 240, 255),
+            },
+        }
+    }
+
+    /// Builds the skin for this theme. Every style loads the font into the
+    /// UI's atlas again, so skins are best made once and kept.
+    pub fn skin(self) -> Skin {
+        let palette = self.palette();
+        let style = || {
+            root_ui()
+                .style_builder()
+                .font(RUBIK)
+                .expect("the bundled font is valid")
+                .font_size(FONT_SIZE)
+                .text_color(palette.text)
+        };
+        let controls = |builder: StyleBuilder| {
+            builder
+                .color(palette.control)
+                .color_hovered(palette.hovered)
+                .color_clicked(palette.clicked)
+                .color_selected(palette.accent)
+                .color_selected_hovered(palette.accent)
+        };
+        let clear = Color::from_rgba(0, 0, 0, 0);
+
+        // a one pixel border around the window's color, stretched to fit
+        let (fill, edge): ([u8; 4], [u8; 4]) = (palette.window.into(), palette.border.into());
+        let mut background = Vec::with_capacity(36);
+        for pixel in 0..9 {
+            background.extend_from_slice(if pixel == 4 { &fill } else { &edge });
+        }
+
+        Skin {
+            label_style: style().margin(RectOffset::new(2., 2., 2., 2.)).build(),
+            button_style: controls(style())
+                .margin(RectOffset::new(8., 8., 2., 2.))
+                .build(),
+            tabbar_style: controls(style())
+                .margin(RectOffset::new(2., 2., 2., 2.))
+                .build(),
+            window_style: style()
+                .background(Image {
+                    width: 3,
+                    height: 3,
+                    bytes: background,
+                })
+                .background_margin(RectOffset::new(1., 1., 1., 1.))
+                .color_inactive(Color::from_rgba(255, 255, 255, 200))
+                .build(),
+            window_titlebar_style: style()
+                .color(palette.border)
+                .color_inactive(palette.control)
+                .build(),
+            editbox_style: style()
+                .color(palette.field)
+                .color_hovered(palette.field)
+                .color_clicked(palette.field)
+                .color_selected(palette.selection)
+                .build(),
+            scrollbar_style: style().color(palette.control).build(),
+            scrollbar_handle_style: controls(style()).build(),
+            checkbox_style: controls(style()).build(),
+            // groups only lay out other widgets, so they aren't drawn
+            group_style: style()
+                .color(clear)
+                .color_hovered(clear)
+                .color_selected(clear)
+                .color_selected_hovered(clear)
+                .build(),
+            title_height: 18.,
+            ..root_ui().default_skin()
+        }
+    }
+}
+
+impl fmt::Display for Theme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Theme::Dark => "dark",
+            Theme::Light => "light",
+        })
+    }
+}
+
+/// Keeps the skin of every theme, and shows the chosen one.
+pub struct Skins {
+    skins: Vec<Skin>,
+    theme: Theme,
+}
+
+impl Skins {
+    pub fn new(theme: Theme) -> Self {
+        let skins = THEMES.iter().map(|theme| theme.skin()).collect();
+        let skins = Skins { skins, theme };
+        root_ui().push_skin(skins.current());
+        skins
+    }
+
+    pub fn theme(&self) -> Theme {
+        self.theme
+    }
+
+    pub fn set(&mut self, theme: Theme) {
+        if theme != self.theme {
+            self.theme = theme;
+            root_ui().pop_skin();
+            root_ui().push_skin(self.current());
+        }
+    }
+
+    fn current(&self) -> &Skin {
+        let index = THEMES
+            .iter()
+            .position(|&theme| theme == self.theme)
+            .unwrap();
+        &self.skins[index]
+    }
+}
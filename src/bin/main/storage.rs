@@ -0,0 +1,38 @@
+//! Keeps text between runs: in files natively, and in the browser's
+//! localStorage on the web, where `name` is the storage key.
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn write(name: &str, text: &str) -> Result<(), &'static str> {
+    std::fs::write(name, text).map_err(|_| "could not write file")
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read(name: &str) -> Result<String, &'static str> {
+    std::fs::read_to_string(name).map_err(|_| "could not read file")
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn write(name: &str, text: &str) -> Result<(), &'static str> {
+    if web::storage_set(name, text) {
+        Ok(())
+    } else {
+        Err("could not write to local storage")
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn read(name: &str) -> Result<String, &'static str> {
+    web::storage_get(name).ok_or("nothing saved in local storage")
+}
+
+#[cfg(target_arch = "wasm32")]
+mod web {
+    use wasm_bindgen::prelude::*;
+
+    #[wasm_bindgen]
+    extern "C" {
+        /// Defined in `index.html`.
+        pub fn storage_set(key: &str, value: &str) -> bool;
+        pub fn storage_get(key: &str) -> Option<String>;
+    }
+}
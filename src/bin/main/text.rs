@@ -0,0 +1,73 @@
+use life::{pattern::Pattern, text::Font};
+use macroquad::{
+    prelude::*,
+    ui::{hash, root_ui, widgets},
+};
+
+pub const MIN_HEIGHT: usize = 4;
+pub const MAX_HEIGHT: usize = 64;
+
+/// The window for writing text into the world with the bundled font. The
+/// text is drawn again only when it or its height changes.
+pub struct TextStamp {
+    font: Font<'static>,
+    text: String,
+    height: f32,
+    drawn: Option<(String, usize, Result<Pattern, &'static str>)>,
+}
+
+impl TextStamp {
+    pub fn new() -> Self {
+        TextStamp {
+            font: Font::rubik(),
+            text: "Life".to_owned(),
+            height: 16.,
+            drawn: None,
+        }
+    }
+
+    /// Shows the window, returning the text's pattern when it's stamped.
+    pub fn ui(&mut self) -> Option<Pattern> {
+        let mut stamped = None;
+
+        widgets::Window::new(hash!(), vec2(20., 20.), vec2(320., 190.))
+            .movable(true)
+            .label("Text")
+            .ui(&mut root_ui(), |ui| {
+                widgets::Editbox::new(hash!(), vec2(300., 60.))
+                    .multiline(true)
+                    .ui(ui, &mut self.text);
+                ui.slider(
+                    hash!(),
+                    "Height",
+                    MIN_HEIGHT as f32..MAX_HEIGHT as f32,
+                    &mut self.height,
+                );
+
+                let height = self.height.round() as usize;
+                let font = &self.font;
+                let text = &self.text;
+                let drawn = match &mut self.drawn {
+                    Some((was, at, drawn)) if was == text && *at == height => drawn,
+                    slot => {
+                        let drawn = font.pattern(text, height);
+                        &mut slot.insert((text.clone(), height, drawn)).2
+                    }
+                };
+                ui.label(
+                    None,
+                    &match drawn {
+                        Ok(pattern) => {
+                            format!("{} by {} cells", pattern.size().0, pattern.size().1)
+                        }
+                        Err(e) => e.to_string(),
+                    },
+                );
+                if ui.button(None, "Stamp") {
+                    stamped = drawn.as_ref().ok().cloned();
+                }
+            });
+
+        stamped
+    }
+}
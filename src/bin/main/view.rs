@@ -0,0 +1,211 @@
+use macroquad::prelude::*;
+
+/// How much one wheel notch or key press changes the zoom.
+const ZOOM_STEP: f32 = 1.25;
+const MIN_ZOOM: f32 = 1. / 64.;
+const MAX_ZOOM: f32 = 64.;
+/// Screen pixels per second that the arrow keys pan by.
+const PAN_SPEED: f32 = 600.;
+
+/// The camera looking at the world: which cell sits in the middle of the
+/// screen and how many pixels each cell covers. Below a zoom of one, several
+/// cells share each pixel.
+pub struct View {
+    center: Vec2,
+    zoom: f32,
+    target_zoom: f32,
+    /// The screen position that stays put while zooming.
+    anchor: Vec2,
+    drag: Option<Vec2>,
+    pinch: Option<(Vec2, f32)>,
+}
+
+impl View {
+    pub fn new(grid_size: (usize, usize), zoom: f32) -> Self {
+        View {
+            center: vec2(grid_size.0 as f32 / 2., grid_size.1 as f32 / 2.),
+            zoom,
+            target_zoom: zoom,
+            anchor: screen_center(),
+            drag: None,
+            pinch: None,
+        }
+    }
+
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    /// The point of the world in the middle of the screen.
+    pub fn center(&self) -> Vec2 {
+        self.center
+    }
+
+    /// Puts `center` in the middle of the screen.
+    pub fn look_at(&mut self, center: Vec2) {
+        self.center = center;
+    }
+
+    /// Zooms so that the whole world fits on the screen.
+    pub fn fit(&mut self, grid_size: (usize, usize)) {
+        self.center = vec2(grid_size.0 as f32 / 2., grid_size.1 as f32 / 2.);
+        let zoom = (screen_width() / grid_size.0 as f32).min(screen_height() / grid_size.1 as f32);
+        self.zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+        self.target_zoom = self.zoom;
+    }
+
+    /// Keeps the world covering the same part of the window after the window
+    /// changes from the `old` size to the current one.
+    pub fn rescale(&mut self, old: Vec2) {
+        let factor = (screen_width() / old.x).min(screen_height() / old.y);
+        self.zoom = (self.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+        self.target_zoom = (self.target_zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+    }
+
+    /// Follows the cells after the world moves them by `offset`.
+    pub fn shift(&mut self, offset: (isize, isize)) {
+        self.center += vec2(offset.0 as f32, offset.1 as f32);
+    }
+
+    pub fn world_to_screen(&self, point: Vec2) -> Vec2 {
+        (point - self.center) * self.zoom + screen_center()
+    }
+
+    pub fn screen_to_world(&self, point: Vec2) -> Vec2 {
+        (point - screen_center()) / self.zoom + self.center
+    }
+
+    /// The cell under a point on the screen, if there is one.
+    pub fn cell_at(&self, point: Vec2, grid_size: (usize, usize)) -> Option<(usize, usize)> {
+        let world = self.screen_to_world(point);
+        if world.x < 0. || world.y < 0. {
+            return None;
+        }
+
+        let cell = (world.x as usize, world.y as usize);
+        if cell.0 < grid_size.0 && cell.1 < grid_size.1 {
+            Some(cell)
+        } else {
+            None
+        }
+    }
+
+    /// The cell closest to the middle of the screen.
+    pub fn center_cell(&self, grid_size: (usize, usize)) -> (usize, usize) {
+        let center = self.center.max(vec2(0., 0.));
+        (
+            (center.x as usize).min(grid_size.0.saturating_sub(1)),
+            (center.y as usize).min(grid_size.1.saturating_sub(1)),
+        )
+    }
+
+    /// The range of cells that are at least partly on the screen.
+    pub fn visible(&self, grid_size: (usize, usize)) -> ((usize, usize), (usize, usize)) {
+        let min = self.screen_to_world(vec2(0., 0.)).max(vec2(0., 0.));
+        let max = self.screen_to_world(vec2(screen_width(), screen_height()));
+
+        (
+            (min.x as usize, min.y as usize),
+            (
+                (max.x.ceil().max(0.) as usize).min(grid_size.0),
+                (max.y.ceil().max(0.) as usize).min(grid_size.1),
+            ),
+        )
+    }
+
+    /// Handles the keys that zoom by a step or reset the view. Returns whether
+    /// the key was used.
+    pub fn handle_key(&mut self, key: KeyCode, grid_size: (usize, usize)) -> bool {
+        match key {
+            KeyCode::Equal | KeyCode::KpAdd => self.zoom_by(ZOOM_STEP, screen_center()),
+            KeyCode::Minus | KeyCode::KpSubtract => self.zoom_by(1. / ZOOM_STEP, screen_center()),
+            KeyCode::Home => self.fit(grid_size),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Follows the mouse wheel, middle button drags, two finger gestures and
+    /// Shift with the arrow keys, and eases towards the requested zoom.
+    pub fn update(&mut self) {
+        let (_, wheel) = mouse_wheel();
+        if wheel != 0. {
+            let mouse = Vec2::from(mouse_position());
+            self.zoom_by(ZOOM_STEP.powf(wheel.signum()), mouse);
+        }
+
+        let mouse = Vec2::from(mouse_position());
+        if is_mouse_button_down(MouseButton::Middle) {
+            if let Some(last) = self.drag {
+                self.center -= (mouse - last) / self.zoom;
+            }
+            self.drag = Some(mouse);
+        } else {
+            self.drag = None;
+        }
+
+        if is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift) {
+            let mut direction = vec2(0., 0.);
+            if is_key_down(KeyCode::Left) {
+                direction.x -= 1.;
+            }
+            if is_key_down(KeyCode::Right) {
+                direction.x += 1.;
+            }
+            if is_key_down(KeyCode::Up) {
+                direction.y -= 1.;
+            }
+            if is_key_down(KeyCode::Down) {
+                direction.y += 1.;
+            }
+            self.center += direction * PAN_SPEED * get_frame_time() / self.zoom;
+        }
+
+        self.update_pinch();
+
+        // ease towards the target in log space so zooming in and out feel alike
+        let (current, target) = (self.zoom.ln(), self.target_zoom.ln());
+        let eased = current + (target - current) * (get_frame_time() * 12.).min(1.);
+        if (eased - target).abs() < 1e-3 {
+            self.set_zoom(self.target_zoom, self.anchor);
+        } else {
+            self.set_zoom(eased.exp(), self.anchor);
+        }
+    }
+
+    /// Two fingers pan with their midpoint and zoom with their spread.
+    fn update_pinch(&mut self) {
+        let touches = touches();
+        if touches.len() != 2 {
+            self.pinch = None;
+            return;
+        }
+
+        let (a, b) = (touches[0].position, touches[1].position);
+        let middle = (a + b) / 2.;
+        let spread = a.distance(b).max(1.);
+
+        if let Some((last_middle, last_spread)) = self.pinch {
+            self.center -= (middle - last_middle) / self.zoom;
+            self.zoom_by(spread / last_spread, middle);
+            self.set_zoom(self.target_zoom, middle);
+        }
+        self.pinch = Some((middle, spread));
+    }
+
+    /// Changes the zoom while keeping the cell under `anchor` in place.
+    fn set_zoom(&mut self, zoom: f32, anchor: Vec2) {
+        let anchored = self.screen_to_world(anchor);
+        self.zoom = zoom;
+        self.center = anchored - (anchor - screen_center()) / zoom;
+    }
+
+    fn zoom_by(&mut self, factor: f32, anchor: Vec2) {
+        self.target_zoom = (self.target_zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+        self.anchor = anchor;
+    }
+}
+
+fn screen_center() -> Vec2 {
+    vec2(screen_width() / 2., screen_height() / 2.)
+}
@@ -0,0 +1,242 @@
+//! Draws patterns to PNG images or animated GIFs without opening a window,
+//! for documentation and scripts.
+
+use std::process::exit;
+
+use life::{
+    cli,
+    grid::Grid,
+    image::{self, Style},
+    pattern::Pattern,
+    LifeLike,
+};
+
+const USAGE: &str = "usage: render [OPTIONS] --out PATH [PATTERN]
+
+Draws a pattern to a PNG image, or to an animated GIF of several generations
+if PATH ends in .gif.
+
+Options:
+    --pattern PATH      Pattern to draw, plaintext (.cells) or RLE (.rle)
+    --apgcode CODE      Pattern to draw, as an apgcode like xq4_153
+    --image PATH        Pattern to draw, from a PNG picture with dark pixels
+                        alive
+    --image-scale S     Cells per pixel of the picture (default: 1)
+    --dither MODE       Turn shades into cells by threshold (threshold), error
+                        diffusion (floyd-steinberg), or a Bayer matrix
+                        (ordered); default: threshold
+    --threshold N       Gray level, from 0 to 255, that dark pixels are below
+                        (default: 128)
+    --invert            Make light pixels alive instead of dark ones
+    --text TEXT         Pattern to draw, from text in the bundled font, where
+                        \\n starts a new line
+    --text-height N     Height of each line of text in cells, from 1 to 1024
+                        (default: 16)
+    --rule RULE         Rule to simulate, like B3/S23 (default)
+    --out PATH          File to write, .png or .gif
+    --margin N          Dead cells around the pattern (default: 4); the world
+                        wraps around at its edges
+    --size WxH          World size in cells, with the pattern in the middle,
+                        instead of a margin; at most 2^28 cells
+    --skip N            Generations to run before drawing (default: 0)
+    --frames N          Generations in a GIF (default: 60)
+    --delay N           Hundredths of a second each GIF frame shows (default: 5)
+    --cell N            Pixels along each side of a cell, from 1 to 1024
+                        (default: 8)
+    --alive RRGGBB      Color of live cells (default: ffffff)
+    --dead RRGGBB       Color of dead cells (default: 000000)
+    --grid RRGGBB       Draw lines between cells in this color
+    --help              Show this message";
+
+/// The largest cells that can be drawn, in pixels.
+const MAX_CELL_SIZE: usize = 1024;
+/// The most cells a world can have.
+const MAX_CELLS: usize = 1 << 28;
+
+struct Options {
+    pattern: Option<Pattern>,
+    rule: LifeLike,
+    out: Option<String>,
+    margin: usize,
+    size: Option<(usize, usize)>,
+    skip: usize,
+    frames: usize,
+    delay: u16,
+    style: Style,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = Options {
+            pattern: None,
+            rule: LifeLike::new("B3/S23").unwrap(),
+            out: None,
+            margin: 4,
+            size: None,
+            skip: 0,
+            frames: 60,
+            delay: 5,
+            style: Style {
+                cell_size: 8,
+                ..Style::default()
+            },
+        };
+        // the picture and text are read once all of their options are known
+        let mut picture = cli::Picture::default();
+        let mut text = cli::Text::default();
+
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("missing value for {}", arg))
+            };
+
+            if picture.parse_arg(&arg, &mut value)? || text.parse_arg(&arg, &mut value)? {
+                continue;
+            }
+            match arg.as_str() {
+                "--pattern" => options.pattern = Some(cli::read_pattern(&value()?)?),
+                "--apgcode" => {
+                    let code = value()?;
+                    options.pattern = Some(
+                        Pattern::from_apgcode(&code)
+                            .map_err(|e| format!("invalid apgcode {}: {}", code, e))?,
+                    );
+                }
+                "--rule" => options.rule = cli::parse_rule(&value()?)?,
+                "--out" => options.out = Some(value()?),
+                "--margin" => options.margin = parse_number("--margin", &value()?)?,
+                "--size" => {
+                    options.size = Some(cli::parse_size("--size", &value()?, MAX_CELLS)?);
+                }
+                "--skip" => options.skip = parse_number("--skip", &value()?)?,
+                "--frames" => options.frames = parse_number::<usize>("--frames", &value()?)?.max(1),
+                "--delay" => options.delay = parse_number("--delay", &value()?)?,
+                "--cell" => {
+                    let cell = value()?;
+                    options.style.cell_size = cell
+                        .parse()
+                        .ok()
+                        .filter(|n| (1..=MAX_CELL_SIZE).contains(n))
+                        .ok_or_else(|| {
+                            format!(
+                                "invalid value for --cell: expected 1 to {}, got {}",
+                                MAX_CELL_SIZE, cell
+                            )
+                        })?;
+                }
+                "--alive" => options.style.alive = parse_color("--alive", &value()?)?,
+                "--dead" => options.style.dead = parse_color("--dead", &value()?)?,
+                "--grid" => options.style.grid_lines = Some(parse_color("--grid", &value()?)?),
+                flag if flag.starts_with('-') => {
+                    return Err(format!("unknown option {}\n\n{}", flag, USAGE))
+                }
+                path => options.pattern = Some(cli::read_pattern(path)?),
+            }
+        }
+
+        if let Some(pattern) = picture.read()? {
+            options.pattern = Some(pattern);
+        }
+        if let Some(pattern) = text.pattern()? {
+            options.pattern = Some(pattern);
+        }
+
+        Ok(options)
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| {
+        format!(
+            "invalid value for {}: expected a number, got {}",
+            name, value
+        )
+    })
+}
+
+fn parse_color(name: &str, value: &str) -> Result<[u8; 3], String> {
+    let hex = value.trim_start_matches('#');
+    let channel = |i: usize| {
+        hex.get(i..i + 2)
+            .and_then(|channel| u8::from_str_radix(channel, 16).ok())
+    };
+    match (hex.len(), channel(0), channel(2), channel(4)) {
+        (6, Some(red), Some(green), Some(blue)) => Ok([red, green, blue]),
+        _ => Err(format!(
+            "invalid value for {}: expected a color like ff8800, got {}",
+            name, value
+        )),
+    }
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{}", USAGE);
+        return;
+    }
+
+    if let Err(message) = Options::parse(args.into_iter()).and_then(|options| render(&options)) {
+        eprintln!("{}", message);
+        exit(2);
+    }
+}
+
+fn render(options: &Options) -> Result<(), String> {
+    let out = options
+        .out
+        .as_ref()
+        .ok_or_else(|| format!("missing --out\n\n{}", USAGE))?;
+    let pattern = options
+        .pattern
+        .as_ref()
+        .ok_or_else(|| format!("missing a pattern\n\n{}", USAGE))?;
+
+    let mut grid = match options.size {
+        Some(size) => {
+            let mut grid = Grid::new(size);
+            pattern
+                .calc_midpoint_placement(size)
+                .and_then(|position| pattern.place(grid.cells_mut(), size, position))
+                .map_err(|e| format!("could not place pattern: {}", e))?;
+            grid
+        }
+        None => {
+            let padded = |side: usize| options.margin.checked_mul(2)?.checked_add(side);
+            let (width, height) = pattern.size();
+            match (padded(width), padded(height)) {
+                (Some(width), Some(height)) if matches!(width.checked_mul(height), Some(cells) if cells <= MAX_CELLS) =>
+                    {}
+                _ => {
+                    return Err(format!(
+                        "invalid value for --margin: the world would have more than {} cells",
+                        MAX_CELLS
+                    ))
+                }
+            }
+            Grid::from_pattern(pattern, options.margin)
+        }
+    };
+    for _ in 0..options.skip {
+        grid.step(&options.rule);
+    }
+
+    let size = grid.size();
+    let bytes = if out.to_ascii_lowercase().ends_with(".gif") {
+        image::gif(
+            &mut grid,
+            &options.rule,
+            options.frames,
+            (0, 0),
+            size,
+            &options.style,
+            options.delay,
+        )
+    } else {
+        image::png(&grid, (0, 0), size, &options.style)
+    }
+    .map_err(|e| format!("could not draw {}: {}", out, e))?;
+
+    std::fs::write(out, bytes).map_err(|e| format!("could not write {}: {}", out, e))
+}
@@ -0,0 +1,74 @@
+use life::grid::Grid;
+
+/// How cells are packed into characters.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// Two cells per character, one above the other, with `▀`, `▄` and `█`.
+    HalfBlocks,
+    /// Eight cells per character, two wide and four high, with braille
+    /// patterns.
+    Braille,
+}
+
+/// The bit of a braille pattern (from U+2800) for each dot, by row and
+/// column. The dots were numbered for six-dot braille first, so the bottom
+/// row comes last.
+const BRAILLE_DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+impl Mode {
+    pub fn toggled(self) -> Self {
+        match self {
+            Mode::HalfBlocks => Mode::Braille,
+            Mode::Braille => Mode::HalfBlocks,
+        }
+    }
+
+    /// The columns and rows of cells each character covers.
+    pub fn cells_per_char(self) -> (usize, usize) {
+        match self {
+            Mode::HalfBlocks => (1, 2),
+            Mode::Braille => (2, 4),
+        }
+    }
+
+    /// Draws the cells in the top left corner of the world that fit into
+    /// `columns` by `rows` characters, as lines of text.
+    pub fn render(self, grid: &Grid, columns: usize, rows: usize) -> Vec<String> {
+        let (width, height) = self.cells_per_char();
+        let size = grid.size();
+        let alive = |x: usize, y: usize| x < size.0 && y < size.1 && grid.get((x, y));
+
+        (0..rows)
+            .map(|row| {
+                (0..columns)
+                    .map(|column| {
+                        let (x, y) = (column * width, row * height);
+                        match self {
+                            Mode::HalfBlocks => match (alive(x, y), alive(x, y + 1)) {
+                                (false, false) => ' ',
+                                (true, false) => '▀',
+                                (false, true) => '▄',
+                                (true, true) => '█',
+                            },
+                            Mode::Braille => {
+                                let mut dots = 0;
+                                for (dy, bits) in BRAILLE_DOTS.iter().enumerate() {
+                                    for (dx, bit) in bits.iter().enumerate() {
+                                        if alive(x + dx, y + dy) {
+                                            dots |= bit;
+                                        }
+                                    }
+                                }
+                                // a blank pattern would be narrower in some fonts
+                                match dots {
+                                    0 => ' ',
+                                    dots => char::from_u32(0x2800 + dots).unwrap(),
+                                }
+                            }
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,263 @@
+//! Runs the simulation in a terminal, for when there's no window to open, like
+//! over SSH. Cells are drawn with half blocks or braille characters.
+
+mod draw;
+#[cfg(unix)]
+mod term;
+
+use std::time::{Duration, Instant};
+
+use ::rand::{rngs::StdRng, Rng, SeedableRng};
+use life::{
+    cli,
+    grid::Grid,
+    history::{self, History},
+    pattern::Pattern,
+    speed::Speed,
+    LifeLike,
+};
+
+use draw::Mode;
+
+const USAGE: &str = "usage: tui [OPTIONS] [PATTERN]
+
+Options:
+    --pattern PATH      Start from a plaintext (.cells) or RLE (.rle) pattern
+    --rule RULE         Rule to simulate, like B3/S23
+    --size WxH          World size in cells, at most 16777216 in all (default:
+                        fill the terminal)
+    --speed N           Run at 60 * 2^N generations per second, for N from -6
+                        to 12 (default: 0)
+    --seed N            Seed for random soups
+    --braille           Draw eight cells per character with braille instead of
+                        two with half blocks
+    --paused            Start with the simulation paused
+    --help              Show this message
+
+Keys:
+    Space               Pause or resume
+    Right, Left         Step forward or back while paused
+    . ,                 Double or halve the speed
+    0                   Reset the speed to 60 generations per second
+    N                   Fill the world with a random soup
+    B                   Switch between half blocks and braille
+    Q, Escape, Ctrl+C   Quit";
+
+/// The most cells a world can have.
+const MAX_CELLS: usize = 1 << 24;
+/// How often the screen is redrawn.
+const FRAME: Duration = Duration::from_millis(33);
+/// The longest a frame may spend stepping, so that keys are still read when
+/// the target rate can't be reached.
+const FRAME_BUDGET: Duration = Duration::from_millis(25);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum State {
+    Normal,
+    Paused,
+}
+
+struct Options {
+    rule: LifeLike,
+    pattern: Option<Pattern>,
+    size: Option<(usize, usize)>,
+    speed: i32,
+    seed: Option<u64>,
+    mode: Mode,
+    paused: bool,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = Options {
+            rule: LifeLike::new("B3/S23").unwrap(),
+            pattern: None,
+            size: None,
+            speed: 0,
+            seed: None,
+            mode: Mode::HalfBlocks,
+            paused: false,
+        };
+
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("missing value for {}", arg))
+            };
+
+            match arg.as_str() {
+                "--pattern" => options.pattern = Some(cli::read_pattern(&value()?)?),
+                "--rule" => options.rule = cli::parse_rule(&value()?)?,
+                "--size" => {
+                    options.size = Some(cli::parse_size("--size", &value()?, MAX_CELLS)?);
+                }
+                "--speed" => options.speed = cli::parse_speed(&value()?)?,
+                "--seed" => {
+                    let seed = value()?;
+                    options.seed = Some(
+                        seed.parse()
+                            .map_err(|_| format!("invalid value for --seed: {}", seed))?,
+                    );
+                }
+                "--braille" => options.mode = Mode::Braille,
+                "--paused" => options.paused = true,
+                flag if flag.starts_with('-') => {
+                    return Err(format!("unknown option {}\n\n{}", flag, USAGE))
+                }
+                path => options.pattern = Some(cli::read_pattern(path)?),
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+#[cfg(not(unix))]
+fn main() {
+    eprintln!("the terminal front end only runs in Unix terminals");
+    std::process::exit(1);
+}
+
+#[cfg(unix)]
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{}", USAGE);
+        return;
+    }
+    let options = Options::parse(args.into_iter()).unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        std::process::exit(2);
+    });
+
+    if let Err(e) = run(options) {
+        eprintln!("terminal error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(unix)]
+fn run(options: Options) -> std::io::Result<()> {
+    let mut terminal = term::Terminal::enter()?;
+    let mut mode = options.mode;
+
+    // without a size, the world fills the terminal above the status line
+    let fit = |mode: Mode, (columns, rows): (usize, usize)| {
+        let (width, height) = mode.cells_per_char();
+        (columns * width, rows.saturating_sub(1).max(1) * height)
+    };
+
+    let mut rng = match options.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let mut grid = Grid::new(options.size.unwrap_or_else(|| fit(mode, terminal.size())));
+    let mut message = None;
+    match &options.pattern {
+        Some(pattern) => {
+            if let Err(e) = pattern
+                .calc_midpoint_placement(grid.size())
+                .and_then(|position| {
+                    let size = grid.size();
+                    pattern.place(grid.cells_mut(), size, position)
+                })
+            {
+                message = Some(format!("could not place pattern: {}", e));
+            }
+        }
+        None => fill_random(&mut grid, &mut rng),
+    }
+
+    let machine = &options.rule;
+    let mut history = History::new(&grid, history::Limits::default());
+    let mut state = if options.paused {
+        State::Paused
+    } else {
+        State::Normal
+    };
+    let mut speed = Speed::new(options.speed);
+    let mut screen = terminal.size();
+    let mut last_frame = Instant::now();
+
+    loop {
+        let start = Instant::now();
+
+        let keys = terminal.keys();
+        if !keys.is_empty() {
+            message = None;
+        }
+        for key in keys {
+            match (state, key) {
+                (_, term::Key::Char('q' | 'Q' | '\u{3}') | term::Key::Escape) => return Ok(()),
+                (State::Normal, term::Key::Char(' ')) => state = State::Paused,
+                (State::Paused, term::Key::Char(' ')) => state = State::Normal,
+                (State::Paused, term::Key::Right) => {
+                    grid.step(machine);
+                    history.record(&grid);
+                }
+                (State::Paused, term::Key::Left) => {
+                    history.undo(&mut grid);
+                }
+                (_, term::Key::Char('.')) => speed.faster(),
+                (_, term::Key::Char(',')) => speed.slower(),
+                (_, term::Key::Char('0')) => speed.set_exponent(0),
+                (_, term::Key::Char('n' | 'N')) => {
+                    fill_random(&mut grid, &mut rng);
+                    history.record(&grid);
+                }
+                (_, term::Key::Char('b' | 'B')) => mode = mode.toggled(),
+                _ => {}
+            }
+        }
+
+        // follow the terminal when it changes size, or when the mode does
+        let size = terminal.size();
+        if size != screen {
+            screen = size;
+            terminal.write("\x1b[2J")?;
+        }
+        if options.size.is_none() && fit(mode, screen) != grid.size() {
+            grid.resize(fit(mode, screen));
+            history.record(&grid);
+        }
+
+        let elapsed = last_frame.elapsed();
+        last_frame = Instant::now();
+        if state == State::Normal {
+            let out_of_time = || start.elapsed() > FRAME_BUDGET;
+            speed.run(elapsed.as_secs_f64(), out_of_time, || {
+                grid.step(machine);
+                history.record(&grid);
+            });
+        }
+
+        let mut frame = String::from("\x1b[H");
+        for line in mode.render(&grid, screen.0, screen.1.saturating_sub(1)) {
+            frame.push_str(&line);
+            frame.push_str("\r\n");
+        }
+        let status = message.clone().unwrap_or_else(|| {
+            format!(
+                "generation {}  population {}  {}  {}{}  [space] pause  [.,] speed  [n] soup  [b] braille  [q] quit",
+                grid.generation(),
+                grid.population(),
+                machine,
+                speed,
+                if state == State::Paused { " (paused)" } else { "" },
+            )
+        });
+        let status: String = status.chars().take(screen.0).collect();
+        frame.push_str(&format!("\x1b[7m{}\x1b[0m\x1b[K", status));
+        terminal.write(&frame)?;
+
+        if let Some(rest) = FRAME.checked_sub(start.elapsed()) {
+            std::thread::sleep(rest);
+        }
+    }
+}
+
+fn fill_random(grid: &mut Grid, rng: &mut impl Rng) {
+    grid.cells_mut()
+        .iter_mut()
+        .for_each(|cell| cell.set(rng.gen()));
+}
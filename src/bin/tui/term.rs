@@ -0,0 +1,115 @@
+//! Just enough terminal handling for the front end: raw input, the alternate
+//! screen, and the window size. It's written with termios and ANSI escape
+//! codes, so it works on Unix terminals (and over SSH) only.
+
+use std::io::{self, Write};
+use std::mem;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Key {
+    Char(char),
+    Left,
+    Right,
+    Escape,
+}
+
+/// The terminal in raw mode, showing the alternate screen. Everything is put
+/// back the way it was when this is dropped.
+pub struct Terminal {
+    original: libc::termios,
+}
+
+impl Terminal {
+    pub fn enter() -> io::Result<Self> {
+        let mut original: libc::termios = unsafe { mem::zeroed() };
+        if unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut original) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        // keys arrive one at a time without echo, Ctrl+C included, and reads
+        // return straight away when no key is waiting
+        let mut raw = original;
+        raw.c_lflag &= !(libc::ICANON | libc::ECHO | libc::ISIG | libc::IEXTEN);
+        raw.c_iflag &= !(libc::IXON | libc::ICRNL);
+        raw.c_cc[libc::VMIN] = 0;
+        raw.c_cc[libc::VTIME] = 0;
+        if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSAFLUSH, &raw) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let mut terminal = Terminal { original };
+        terminal.write("\x1b[?1049h\x1b[?25l\x1b[2J")?;
+        Ok(terminal)
+    }
+
+    /// The size of the terminal in columns and rows, or 80 by 24 if it can't
+    /// be told.
+    pub fn size(&self) -> (usize, usize) {
+        let mut size: libc::winsize = unsafe { mem::zeroed() };
+        let found = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) } == 0;
+        if found && size.ws_col > 0 && size.ws_row > 0 {
+            (size.ws_col as usize, size.ws_row as usize)
+        } else {
+            (80, 24)
+        }
+    }
+
+    /// The keys pressed since the last call.
+    pub fn keys(&mut self) -> Vec<Key> {
+        let mut bytes = Vec::new();
+        let mut buffer = [0u8; 64];
+        loop {
+            let read = unsafe {
+                libc::read(
+                    libc::STDIN_FILENO,
+                    buffer.as_mut_ptr() as *mut libc::c_void,
+                    buffer.len(),
+                )
+            };
+            if read <= 0 {
+                break;
+            }
+            bytes.extend_from_slice(&buffer[..read as usize]);
+        }
+
+        let mut keys = Vec::new();
+        let mut rest = bytes.as_slice();
+        while let Some((&first, tail)) = rest.split_first() {
+            rest = tail;
+            let key = match first {
+                // arrow keys are sent as escape sequences, like `ESC [ C`
+                0x1b => match rest {
+                    [b'[' | b'O', b'C', tail @ ..] => {
+                        rest = tail;
+                        Key::Right
+                    }
+                    [b'[' | b'O', b'D', tail @ ..] => {
+                        rest = tail;
+                        Key::Left
+                    }
+                    [b'[' | b'O', _, tail @ ..] => {
+                        rest = tail;
+                        continue;
+                    }
+                    _ => Key::Escape,
+                },
+                byte => Key::Char(byte as char),
+            };
+            keys.push(key);
+        }
+        keys
+    }
+
+    pub fn write(&mut self, text: &str) -> io::Result<()> {
+        let mut stdout = io::stdout().lock();
+        stdout.write_all(text.as_bytes())?;
+        stdout.flush()
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        let _ = self.write("\x1b[0m\x1b[?25h\x1b[?1049l");
+        unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSAFLUSH, &self.original) };
+    }
+}
@@ -0,0 +1,266 @@
+//! Command line options that more than one of the binaries take.
+//!
+//! Unlike the rest of the crate, errors here are messages for the person
+//! typing the command, so they name the option and the value it was given.
+
+use crate::{
+    image::{self, Dither, Import},
+    pattern::Pattern,
+    speed::{MAX_EXPONENT, MIN_EXPONENT},
+    text::{self, Font},
+    LifeLike,
+};
+
+/// Reads a plaintext (.cells) or RLE (.rle) pattern file.
+pub fn read_pattern(path: &str) -> Result<Pattern, String> {
+    let text =
+        std::fs::read_to_string(path).map_err(|e| format!("could not read {}: {}", path, e))?;
+    Pattern::from_file(path, &text).map_err(|e| format!("invalid pattern {}: {}", path, e))
+}
+
+/// Reads the value of `--rule`.
+pub fn parse_rule(value: &str) -> Result<LifeLike, String> {
+    LifeLike::new(value.trim()).map_err(|e| format!("invalid rule {}: {}", value, e))
+}
+
+/// Reads the value of `--speed`, the exponent of a [`crate::speed::Speed`].
+pub fn parse_speed(value: &str) -> Result<i32, String> {
+    value
+        .parse()
+        .ok()
+        .filter(|n| (MIN_EXPONENT..=MAX_EXPONENT).contains(n))
+        .ok_or_else(|| {
+            format!(
+                "invalid value for --speed: expected {} to {}, got {}",
+                MIN_EXPONENT, MAX_EXPONENT, value
+            )
+        })
+}
+
+/// Reads a size written as `WxH`, like the value of `--size`, with no more
+/// than `max_cells` cells in all.
+pub fn parse_size(name: &str, value: &str, max_cells: usize) -> Result<(usize, usize), String> {
+    let invalid = || format!("invalid value for {}: expected WxH, got {}", name, value);
+    let (width, height) = value.split_once('x').ok_or_else(invalid)?;
+    let size: (usize, usize) = (
+        width.parse().ok().filter(|&n| n > 0).ok_or_else(invalid)?,
+        height.parse().ok().filter(|&n| n > 0).ok_or_else(invalid)?,
+    );
+    match size.0.checked_mul(size.1) {
+        Some(cells) if cells <= max_cells => Ok(size),
+        _ => Err(format!(
+            "invalid value for {}: at most {} cells, got {}",
+            name, max_cells, value
+        )),
+    }
+}
+
+/// The options for starting from a PNG picture: `--image`, and how its
+/// shades become cells. The picture is read with [`Picture::read`] once all
+/// of them are known, since they can come in any order.
+#[derive(Clone, Debug, Default)]
+pub struct Picture {
+    pub path: Option<String>,
+    pub import: Import,
+}
+
+impl Picture {
+    /// Takes `arg` if it's one of the picture options, calling `value` for
+    /// its value if it has one. Returns whether it was.
+    pub fn parse_arg(
+        &mut self,
+        arg: &str,
+        value: impl FnOnce() -> Result<String, String>,
+    ) -> Result<bool, String> {
+        match arg {
+            "--image" => self.path = Some(value()?),
+            "--image-scale" => {
+                let scale = value()?;
+                self.import.scale = scale
+                    .parse()
+                    .ok()
+                    .filter(|&scale: &f32| scale > 0. && scale.is_finite())
+                    .ok_or_else(|| {
+                        format!(
+                            "invalid value for --image-scale: expected a positive number, got {}",
+                            scale
+                        )
+                    })?;
+            }
+            "--dither" => {
+                self.import.dither = match value()?.as_str() {
+                    "threshold" => Dither::Threshold,
+                    "floyd-steinberg" => Dither::FloydSteinberg,
+                    "ordered" => Dither::Ordered,
+                    other => {
+                        return Err(format!(
+                            "invalid value for --dither: expected threshold, floyd-steinberg, or ordered, got {}",
+                            other
+                        ))
+                    }
+                }
+            }
+            "--threshold" => {
+                let threshold = value()?;
+                self.import.threshold = threshold.parse().map_err(|_| {
+                    format!(
+                        "invalid value for --threshold: expected 0 to 255, got {}",
+                        threshold
+                    )
+                })?;
+            }
+            "--invert" => self.import.invert = true,
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// Reads the picture, if `--image` was given.
+    pub fn read(&self) -> Result<Option<Pattern>, String> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(None),
+        };
+        let bytes = std::fs::read(path).map_err(|e| format!("could not read {}: {}", path, e))?;
+        image::import_png(&bytes, &self.import)
+            .map(Some)
+            .map_err(|e| format!("invalid picture {}: {}", path, e))
+    }
+}
+
+/// The options for starting from text: `--text`, where `\n` starts a new
+/// line, and `--text-height`.
+#[derive(Clone, Debug)]
+pub struct Text {
+    pub text: Option<String>,
+    pub height: usize,
+}
+
+impl Default for Text {
+    fn default() -> Self {
+        Text {
+            text: None,
+            height: 16,
+        }
+    }
+}
+
+impl Text {
+    /// Takes `arg` if it's one of the text options, like
+    /// [`Picture::parse_arg`].
+    pub fn parse_arg(
+        &mut self,
+        arg: &str,
+        value: impl FnOnce() -> Result<String, String>,
+    ) -> Result<bool, String> {
+        match arg {
+            "--text" => self.text = Some(value()?),
+            "--text-height" => {
+                let height = value()?;
+                self.height = height
+                    .parse()
+                    .ok()
+                    .filter(|n| (1..=text::MAX_HEIGHT).contains(n))
+                    .ok_or_else(|| {
+                        format!(
+                            "invalid value for --text-height: expected 1 to {}, got {}",
+                            text::MAX_HEIGHT,
+                            height
+                        )
+                    })?;
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// Draws the text in the bundled font, if `--text` was given.
+    pub fn pattern(&self) -> Result<Option<Pattern>, String> {
+        let text = match &self.text {
+            Some(text) => text,
+            None => return Ok(None),
+        };
+        Font::rubik()
+            .pattern(&text.replace("\\n", "\n"), self.height)
+            .map(Some)
+            .map_err(|e| format!("invalid value for --text: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_values() {
+        assert_eq!(parse_rule(" b36/s23 ").unwrap().to_string(), "B36/S23");
+        assert!(parse_rule("B9/S").unwrap_err().contains("B9/S"));
+        assert_eq!(parse_speed("-6"), Ok(-6));
+        assert!(parse_speed("13").is_err());
+        assert!(parse_speed("fast").is_err());
+        assert!(read_pattern("no/such/file.rle").is_err());
+
+        assert_eq!(parse_size("--size", "3x4", 12), Ok((3, 4)));
+        assert!(parse_size("--size", "3x5", 12).is_err());
+        assert!(parse_size("--size", "0x4", 12).is_err());
+        assert!(parse_size("--size", "3", 12).is_err());
+        assert!(parse_size("--size", "5000000000x5000000000", usize::MAX).is_err());
+    }
+
+    #[test]
+    fn parses_picture_options() {
+        let mut picture = Picture::default();
+        let value = |text: &str| {
+            let text = text.to_owned();
+            move || Ok(text)
+        };
+        assert_eq!(picture.parse_arg("--image-scale", value("0.5")), Ok(true));
+        assert_eq!(picture.parse_arg("--dither", value("ordered")), Ok(true));
+        assert_eq!(picture.parse_arg("--threshold", value("64")), Ok(true));
+        assert_eq!(picture.parse_arg("--invert", value("unused")), Ok(true));
+        assert_eq!(picture.parse_arg("--rule", value("B3/S23")), Ok(false));
+        assert_eq!(picture.import.scale, 0.5);
+        assert_eq!(picture.import.dither, Dither::Ordered);
+        assert_eq!(picture.import.threshold, 64);
+        assert!(picture.import.invert);
+        assert_eq!(picture.read(), Ok(None));
+
+        assert!(picture.parse_arg("--image-scale", value("-1")).is_err());
+        assert!(picture.parse_arg("--dither", value("random")).is_err());
+        assert!(picture.parse_arg("--threshold", value("256")).is_err());
+        assert!(picture
+            .parse_arg("--image", || Err("missing value for --image".to_owned()))
+            .is_err());
+        picture.path = Some("no/such/picture.png".to_owned());
+        assert!(picture.read().is_err());
+    }
+
+    #[test]
+    fn parses_text_options() {
+        let mut text = Text::default();
+        assert_eq!(text.pattern(), Ok(None));
+        assert_eq!(
+            text.parse_arg("--text", || Ok("I\\nI".to_owned())),
+            Ok(true)
+        );
+        assert_eq!(
+            text.parse_arg("--text-height", || Ok("24".to_owned())),
+            Ok(true)
+        );
+        assert_eq!(
+            text.parse_arg("--image", || Ok("I.png".to_owned())),
+            Ok(false)
+        );
+        let lines = text.pattern().unwrap().unwrap();
+        assert!(lines.size().1 > 24, "{:?}", lines.size());
+
+        assert!(text
+            .parse_arg("--text-height", || Ok("0".to_owned()))
+            .is_err());
+        assert!(text
+            .parse_arg("--text-height", || Ok("1025".to_owned()))
+            .is_err());
+        text.text = Some("I".repeat(1_000_000));
+        assert!(text.pattern().is_err());
+    }
+}
@@ -0,0 +1,258 @@
+//! Exhaustive enumeration of small still lifes and oscillators.
+//!
+//! Every arrangement of at most `max_cells` cells whose bounding box fits in
+//! the given limits is run on an empty plane for up to `max_period`
+//! generations. Arrangements that return to where they started are kept, and
+//! each object is reduced to a canonical form over its phases and the eight
+//! rotations and reflections of the square, so every object is reported once.
+//! Unions of separate objects, such as a block next to a blinker, count as
+//! objects too.
+
+use std::collections::BTreeMap;
+
+use crate::{pattern::Pattern, LifeLike};
+
+/// Bounding boxes larger than this many cells are refused, since each
+/// arrangement is tried as a 64 bit mask.
+const MAX_AREA: usize = 64;
+/// Searches that would try more arrangements than this are refused, so that
+/// any accepted limits finish in seconds.
+const MAX_CANDIDATES: u128 = 1 << 22;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Limits {
+    /// The largest bounding box to search, in either orientation.
+    pub max_size: (usize, usize),
+    /// The most live cells an object may have.
+    pub max_cells: usize,
+    /// The longest period to look for. Still lifes have period 1.
+    pub max_period: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_size: (4, 4),
+            max_cells: usize::MAX,
+            max_period: 2,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Object {
+    pub period: usize,
+    pub pattern: Pattern,
+}
+
+/// Finds every still life and oscillator of `machine` within `limits`,
+/// ordered by period and then by population.
+pub fn enumerate(machine: &LifeLike, limits: &Limits) -> Result<Vec<Object>, &'static str> {
+    if machine.simulate(false, 0) {
+        return Err("enumeration does not support rules with B0");
+    }
+
+    let (max_width, max_height) = limits.max_size;
+    if !matches!(max_width.checked_mul(max_height), Some(area) if area <= MAX_AREA) {
+        return Err("bounding box is too large to enumerate");
+    }
+
+    // the transposed box covers the same objects, so only search one
+    let boxes: Vec<(usize, usize)> = (1..=max_width)
+        .flat_map(|width| (1..=max_height).map(move |height| (width, height)))
+        .filter(|&(width, height)| !(height < width && height <= max_width && width <= max_height))
+        .collect();
+    let candidates: u128 = boxes
+        .iter()
+        .map(|&(width, height)| candidates(width * height, limits.max_cells))
+        .sum();
+    if candidates > MAX_CANDIDATES {
+        return Err("too many arrangements to enumerate; lower the size or cell limits");
+    }
+
+    let mut found: BTreeMap<Pattern, usize> = BTreeMap::new();
+
+    for (width, height) in boxes {
+        let area = width * height;
+        for count in 1..=limits.max_cells.min(area) {
+            for mask in masks(area, count) {
+                let mut pattern = Pattern::new((width, height));
+                for bit in 0..(width * height) {
+                    pattern.set((bit % width, bit / width), mask & (1 << bit) != 0);
+                }
+                if !fills_bounding_box(&pattern) {
+                    continue;
+                }
+
+                if let Some((period, canonical)) = classify(&pattern, machine, limits.max_period) {
+                    found.entry(canonical).or_insert(period);
+                }
+            }
+        }
+    }
+
+    let mut objects: Vec<Object> = found
+        .into_iter()
+        .map(|(pattern, period)| Object { period, pattern })
+        .collect();
+    objects.sort_by_key(|object| (object.period, object.pattern.population()));
+
+    Ok(objects)
+}
+
+/// How many arrangements of 1 to `max_cells` cells there are in `area` cells.
+fn candidates(area: usize, max_cells: usize) -> u128 {
+    let mut total = 0;
+    let mut choices: u128 = 1;
+    for count in 1..=max_cells.min(area) {
+        choices = choices * (area - count + 1) as u128 / count as u128;
+        total += choices;
+    }
+    total
+}
+
+/// Every mask of `bits` bits with `count` of them set, in increasing order.
+/// `count` must be between 1 and `bits`, which is at most 64.
+fn masks(bits: usize, count: usize) -> impl Iterator<Item = u64> {
+    let first = u64::MAX >> (64 - count);
+    let last = first << (bits - count);
+    std::iter::successors(Some(first), move |&mask| {
+        if mask == last {
+            return None;
+        }
+        // the next larger number with as many ones (Gosper's hack), which
+        // can't overflow before the last mask
+        let lowest = mask.isolate_lowest_one();
+        let ripple = mask + lowest;
+        Some((((ripple ^ mask) >> 2) / lowest) | ripple)
+    })
+}
+
+/// Whether every edge of the pattern has a live cell on it.
+fn fills_bounding_box(pattern: &Pattern) -> bool {
+    let (width, height) = pattern.size();
+    let row = |y| (0..width).any(|x| pattern.get((x, y)));
+    let column = |x| (0..height).any(|y| pattern.get((x, y)));
+
+    row(0) && row(height - 1) && column(0) && column(width - 1)
+}
+
+/// Runs the pattern to see whether it comes back to where it started. If it
+/// does, returns the period and the smallest of its phases and their images.
+/// The pattern must not have empty rows or columns at its edges.
+fn classify(pattern: &Pattern, machine: &LifeLike, max_period: usize) -> Option<(usize, Pattern)> {
+    let mut position = (0isize, 0isize);
+    let mut phases = vec![pattern.clone()];
+
+    for period in 1..=max_period {
+        let (offset, next) = phases[period - 1].evolve(machine).trimmed()?;
+        position.0 += offset.0 as isize - 1;
+        position.1 += offset.1 as isize - 1;
+
+        if position == (0, 0) && &next == pattern {
+            let canonical = phases
+                .iter()
+                .flat_map(|phase| phase.symmetries().to_vec())
+                .min()
+                .expect("there is at least one phase");
+            return Some((period, canonical));
+        }
+
+        phases.push(next);
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn canonical(plaintext: &str) -> Pattern {
+        let pattern = Pattern::from_plaintext(plaintext.lines()).unwrap();
+        pattern.symmetries().iter().min().unwrap().clone()
+    }
+
+    #[test]
+    fn life_objects() {
+        let life = LifeLike::new("B3/S23").unwrap();
+        let objects = enumerate(&life, &Limits::default()).unwrap();
+
+        let still_lifes = [
+            "OO\nOO",
+            ".O.\nO.O\n.O.",
+            ".OO.\nO..O\n.OO.",
+            "OO.\nO.O\n.O.",
+        ];
+        for plaintext in still_lifes.iter() {
+            let pattern = canonical(plaintext);
+            assert_eq!(
+                objects.iter().filter(|o| o.pattern == pattern).count(),
+                1,
+                "{}",
+                plaintext
+            );
+            assert!(objects.contains(&Object { period: 1, pattern }));
+        }
+
+        // the blinker shows up in both phases, but is only reported once
+        let blinker = objects
+            .iter()
+            .filter(|o| o.period == 2 && o.pattern.population() == 3);
+        assert_eq!(blinker.count(), 1);
+        let toad = Pattern::from_plaintext(".OOO\nOOO.".lines()).unwrap();
+        let (period, toad) = classify(&toad, &life, 2).unwrap();
+        assert_eq!(period, 2);
+        assert!(objects.contains(&Object {
+            period,
+            pattern: toad
+        }));
+
+        let mut sorted = objects.clone();
+        sorted.sort_by_key(|object| (object.period, object.pattern.population()));
+        assert_eq!(objects, sorted);
+    }
+
+    #[test]
+    fn respects_limits() {
+        let life = LifeLike::new("B3/S23").unwrap();
+        let limits = Limits {
+            max_size: (3, 3),
+            max_cells: 4,
+            max_period: 1,
+        };
+        let objects = enumerate(&life, &limits).unwrap();
+
+        assert!(objects.iter().all(|o| o.period == 1));
+        assert!(objects.iter().all(|o| o.pattern.population() <= 4));
+        assert_eq!(objects.len(), 2); // block and tub
+
+        let limits = Limits {
+            max_size: (7, 7),
+            ..Limits::default()
+        };
+        assert!(enumerate(&life, &limits).is_err());
+
+        let b0 = LifeLike::new("B0/S").unwrap();
+        assert!(enumerate(&b0, &Limits::default()).is_err());
+
+        // few enough cells make larger boxes searchable
+        let limits = Limits {
+            max_size: (6, 6),
+            max_cells: 4,
+            max_period: 2,
+        };
+        let objects = enumerate(&life, &limits).unwrap();
+        assert_eq!(objects.len(), 3); // block, tub and blinker
+    }
+
+    #[test]
+    fn masks_by_count() {
+        let all: Vec<u64> = masks(4, 2).collect();
+        assert_eq!(all, vec![0b0011, 0b0101, 0b0110, 0b1001, 0b1010, 0b1100]);
+        assert_eq!(masks(64, 1).count(), 64);
+        assert_eq!(masks(64, 64).collect::<Vec<_>>(), vec![u64::MAX]);
+        assert_eq!(candidates(4, 2), 4 + 6);
+        assert_eq!(candidates(4, usize::MAX), 15);
+    }
+}
@@ -0,0 +1,257 @@
+use bitvec::prelude::*;
+
+use crate::{
+    pattern::{self, Pattern},
+    Automata,
+};
+
+/// A toroidal world together with the double buffer and change tracking that
+/// [`Automata::update`] needs to step it.
+///
+/// The grid only recomputes cells that changed in the previous generation (and
+/// their neighbors). Any direct edit through [`Grid::set`] or
+/// [`Grid::cells_mut`] marks the grid dirty, so the next step recomputes every
+/// cell instead of trusting the stale buffer.
+#[derive(Clone, Debug)]
+pub struct Grid {
+    fresh: BitVec<Lsb0, usize>,
+    stale: BitVec<Lsb0, usize>,
+    changes: BitVec<Lsb0, usize>,
+    size: (usize, usize),
+    generation: usize,
+    dirty: bool,
+}
+
+impl Grid {
+    pub fn new(size: (usize, usize)) -> Self {
+        Grid {
+            fresh: BitVec::repeat(false, size.0 * size.1),
+            stale: BitVec::repeat(false, size.0 * size.1),
+            changes: BitVec::repeat(false, size.0 * size.1),
+            size,
+            generation: 0,
+            dirty: true,
+        }
+    }
+
+    /// Creates a grid just big enough to hold `pattern` with `margin` empty
+    /// cells on every side, and places the pattern in the middle.
+    pub fn from_pattern(pattern: &Pattern, margin: usize) -> Self {
+        let (width, height) = pattern.size();
+        let mut grid = Grid::new((width + 2 * margin, height + 2 * margin));
+        pattern
+            .place(&mut grid.fresh, grid.size, (margin, margin))
+            .expect("grid is sized to fit the pattern");
+        grid
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        self.size
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn set_generation(&mut self, generation: usize) {
+        self.generation = generation;
+    }
+
+    pub fn population(&self) -> usize {
+        self.fresh.count_ones()
+    }
+
+    /// The number of cells that changed during the last step.
+    pub fn changed(&self) -> usize {
+        self.fresh
+            .as_raw_slice()
+            .iter()
+            .zip(self.stale.as_raw_slice().iter())
+            .map(|(&a, &b)| (a ^ b).count_ones() as usize)
+            .sum()
+    }
+
+    /// The number of cells that were born during the last step.
+    pub fn births(&self) -> usize {
+        self.fresh
+            .as_raw_slice()
+            .iter()
+            .zip(self.stale.as_raw_slice().iter())
+            .map(|(&a, &b)| (a & !b).count_ones() as usize)
+            .sum()
+    }
+
+    /// The number of cells that died during the last step.
+    pub fn deaths(&self) -> usize {
+        self.fresh
+            .as_raw_slice()
+            .iter()
+            .zip(self.stale.as_raw_slice().iter())
+            .map(|(&a, &b)| (!a & b).count_ones() as usize)
+            .sum()
+    }
+
+    pub fn cells(&self) -> &BitSlice<Lsb0, usize> {
+        &self.fresh
+    }
+
+    /// Mutable access to the current generation. The grid is marked dirty.
+    pub fn cells_mut(&mut self) -> &mut BitSlice<Lsb0, usize> {
+        self.dirty = true;
+        &mut self.fresh
+    }
+
+    pub fn get(&self, cell: (usize, usize)) -> bool {
+        self.fresh[cell.0 + (cell.1 * self.size.0)]
+    }
+
+    pub fn set(&mut self, cell: (usize, usize), value: bool) {
+        self.dirty = true;
+        self.fresh.set(cell.0 + (cell.1 * self.size.0), value);
+    }
+
+    pub fn clear(&mut self) {
+        self.dirty = true;
+        self.fresh.set_all(false);
+    }
+
+    /// Advances the world one generation with `machine`.
+    pub fn step<A: Automata>(&mut self, machine: &A) {
+        if self.dirty {
+            self.changes.set_all(true);
+            self.dirty = false;
+        } else {
+            self.changes
+                .as_mut_raw_slice()
+                .iter_mut()
+                .zip(
+                    self.fresh
+                        .as_raw_slice()
+                        .iter()
+                        .zip(self.stale.as_raw_slice().iter()),
+                )
+                .for_each(|(c, (&a, &b))| *c = a ^ b);
+        }
+
+        machine.update(&self.fresh, &mut self.stale, &self.changes, self.size);
+        std::mem::swap(&mut self.fresh, &mut self.stale);
+        self.generation += 1;
+    }
+
+    /// The smallest rectangle containing every live cell, as the positions of
+    /// its upper left and lower right corners. This does not account for
+    /// patterns that wrap around the edges of the world.
+    pub fn bounding_box(&self) -> Option<((usize, usize), (usize, usize))> {
+        pattern::bounding_box(&self.fresh, self.size)
+    }
+
+    /// Changes the size of the world, keeping the existing cells anchored at
+    /// the center. Cells that no longer fit are dropped. Returns how far the
+    /// remaining cells moved.
+    pub fn resize(&mut self, size: (usize, usize)) -> (isize, isize) {
+        let mut fresh: BitVec<Lsb0, usize> = BitVec::repeat(false, size.0 * size.1);
+
+        let offset = |old: usize, new: usize| (new as isize - old as isize) / 2;
+        let offset = (offset(self.size.0, size.0), offset(self.size.1, size.1));
+
+        for index in self.fresh.iter_ones() {
+            let x = (index % self.size.0) as isize + offset.0;
+            let y = (index / self.size.0) as isize + offset.1;
+            if (0..size.0 as isize).contains(&x) && (0..size.1 as isize).contains(&y) {
+                fresh.set(x as usize + (y as usize * size.0), true);
+            }
+        }
+
+        self.fresh = fresh;
+        self.stale = BitVec::repeat(false, size.0 * size.1);
+        self.changes = BitVec::repeat(false, size.0 * size.1);
+        self.size = size;
+        self.dirty = true;
+
+        offset
+    }
+
+    /// Copies the live part of the world out as a pattern, along with the
+    /// position of its upper left corner.
+    pub fn to_pattern(&self) -> Option<((usize, usize), Pattern)> {
+        let (min, max) = self.bounding_box()?;
+        let pattern = Pattern::from_region(
+            &self.fresh,
+            self.size,
+            min,
+            (max.0 - min.0 + 1, max.1 - min.1 + 1),
+        )
+        .expect("bounding box lies inside the world");
+        Some((min, pattern))
+    }
+
+    /// Moves every live cell so that the bounding box sits in the middle of a
+    /// world of the given size.
+    pub fn recenter(&mut self, size: (usize, usize)) -> Result<(), &'static str> {
+        let mut grid = Grid::new(size);
+        grid.generation = self.generation;
+
+        if let Some((_, pattern)) = self.to_pattern() {
+            let position = pattern.calc_midpoint_placement(size)?;
+            pattern.place(&mut grid.fresh, size, position)?;
+        }
+
+        *self = grid;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::LifeLike;
+
+    #[test]
+    fn blinker_oscillates() {
+        let life = LifeLike::new("B3/S23").unwrap();
+        let blinker = Pattern::from_plaintext("OOO".lines()).unwrap();
+        let mut grid = Grid::from_pattern(&blinker, 2);
+        let start = grid.cells().to_bitvec();
+
+        grid.step(&life);
+        assert_eq!(grid.population(), 3);
+        assert_eq!(grid.changed(), 4);
+        assert_eq!((grid.births(), grid.deaths()), (2, 2));
+        assert_ne!(grid.cells(), start.as_bitslice());
+
+        grid.step(&life);
+        assert_eq!(grid.cells(), start.as_bitslice());
+        assert_eq!(grid.generation(), 2);
+    }
+
+    #[test]
+    fn edits_are_simulated() {
+        let life = LifeLike::new("B3/S23").unwrap();
+        let mut grid = Grid::new((6, 6));
+        grid.step(&life);
+
+        // a block drawn between steps must survive even though nothing changed
+        for cell in [(2, 2), (3, 2), (2, 3), (3, 3)].iter() {
+            grid.set(*cell, true);
+        }
+        grid.step(&life);
+        grid.step(&life);
+
+        assert_eq!(grid.population(), 4);
+        assert_eq!(grid.bounding_box(), Some(((2, 2), (3, 3))));
+    }
+
+    #[test]
+    fn resize_keeps_center() {
+        let mut grid = Grid::new((4, 4));
+        grid.set((1, 1), true);
+        assert_eq!(grid.resize((8, 6)), (2, 1));
+
+        assert_eq!(grid.bounding_box(), Some(((3, 2), (3, 2))));
+
+        grid.set((0, 0), true);
+        grid.resize((4, 4));
+        assert_eq!(grid.population(), 1);
+        assert_eq!(grid.bounding_box(), Some(((1, 1), (1, 1))));
+    }
+}
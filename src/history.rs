@@ -0,0 +1,247 @@
+//! A bounded record of earlier states of a [`Grid`], for stepping backwards
+//! and undoing edits.
+//!
+//! Each entry stores what changed between one recorded state and the next as
+//! an XOR mask, since applying the same mask again turns the newer state back
+//! into the older one. Masks with few changed cells are kept as a list of
+//! indices; busier ones, like a fresh soup, as a plain bit vector.
+
+use std::collections::VecDeque;
+use std::mem::{size_of, size_of_val};
+
+use bitvec::prelude::*;
+
+use crate::grid::Grid;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Limits {
+    /// The most entries to keep.
+    pub max_entries: usize,
+    /// The most memory the entries may use, in bytes.
+    pub max_bytes: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_entries: 10_000,
+            max_bytes: 64 * 1024 * 1024,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Delta {
+    Sparse(Vec<u32>),
+    Dense(BitVec<Lsb0, usize>),
+}
+
+impl Delta {
+    fn between(old: &BitSlice<Lsb0, usize>, new: &BitSlice<Lsb0, usize>) -> Self {
+        let mut mask = old.to_bitvec();
+        xor(&mut mask, new);
+
+        let changed = mask.count_ones();
+        if changed * size_of::<u32>() < size_of_val(mask.as_raw_slice()) {
+            Delta::Sparse(mask.iter_ones().map(|index| index as u32).collect())
+        } else {
+            Delta::Dense(mask)
+        }
+    }
+
+    fn apply(&self, cells: &mut BitSlice<Lsb0, usize>) {
+        match self {
+            Delta::Sparse(indices) => {
+                for &index in indices {
+                    let index = index as usize;
+                    let value = !cells[index];
+                    cells.set(index, value);
+                }
+            }
+            Delta::Dense(mask) => *cells ^= mask.iter().by_val(),
+        }
+    }
+
+    fn bytes(&self) -> usize {
+        match self {
+            Delta::Sparse(indices) => indices.len() * size_of::<u32>(),
+            Delta::Dense(mask) => size_of_val(mask.as_raw_slice()),
+        }
+    }
+}
+
+/// XORs `mask` into `cells` a word at a time. Both start at the beginning of
+/// their words, so the words line up.
+fn xor(cells: &mut BitVec<Lsb0, usize>, mask: &BitSlice<Lsb0, usize>) {
+    cells
+        .as_mut_raw_slice()
+        .iter_mut()
+        .zip(mask.as_raw_slice().iter())
+        .for_each(|(cell, &mask)| *cell ^= mask);
+}
+
+#[derive(Clone, Debug)]
+struct Entry {
+    /// The generation of the older state.
+    generation: usize,
+    delta: Delta,
+}
+
+#[derive(Clone, Debug)]
+pub struct History {
+    limits: Limits,
+    entries: VecDeque<Entry>,
+    bytes: usize,
+    /// The last recorded state, which the newest entry leads to.
+    current: BitVec<Lsb0, usize>,
+    size: (usize, usize),
+    generation: usize,
+}
+
+impl History {
+    /// Starts an empty history at the grid's current state.
+    pub fn new(grid: &Grid, limits: Limits) -> Self {
+        History {
+            limits,
+            entries: VecDeque::new(),
+            bytes: 0,
+            current: grid.cells().to_bitvec(),
+            size: grid.size(),
+            generation: grid.generation(),
+        }
+    }
+
+    /// How many states can be stepped back to.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Forgets every earlier state, starting over at the grid's current one.
+    pub fn clear(&mut self, grid: &Grid) {
+        *self = History::new(grid, self.limits);
+    }
+
+    /// Remembers the state the grid was in when last recorded, if it has
+    /// changed since. Call this after every step and after edits. A world that
+    /// changed size can't be stepped back across, so the history starts over.
+    pub fn record(&mut self, grid: &Grid) {
+        if grid.size() != self.size {
+            self.clear(grid);
+            return;
+        }
+
+        if grid.cells() == self.current.as_bitslice() && grid.generation() == self.generation {
+            return;
+        }
+
+        let delta = Delta::between(&self.current, grid.cells());
+        self.bytes += delta.bytes();
+        self.entries.push_back(Entry {
+            generation: self.generation,
+            delta,
+        });
+
+        self.current.copy_from_bitslice(grid.cells());
+        self.generation = grid.generation();
+
+        while self.entries.len() > self.limits.max_entries || self.bytes > self.limits.max_bytes {
+            match self.entries.pop_front() {
+                Some(oldest) => self.bytes -= oldest.delta.bytes(),
+                None => break,
+            }
+        }
+    }
+
+    /// Puts the grid back into the state before the last recorded change,
+    /// first recording anything that changed since. Returns whether there was
+    /// a state to go back to.
+    pub fn undo(&mut self, grid: &mut Grid) -> bool {
+        self.record(grid);
+
+        let entry = match self.entries.pop_back() {
+            Some(entry) => entry,
+            None => return false,
+        };
+        self.bytes -= entry.delta.bytes();
+
+        entry.delta.apply(grid.cells_mut());
+        entry.delta.apply(&mut self.current);
+        grid.set_generation(entry.generation);
+        self.generation = entry.generation;
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{pattern::Pattern, LifeLike};
+
+    #[test]
+    fn steps_back_to_the_start() {
+        let life = LifeLike::new("B3/S23").unwrap();
+        let r = Pattern::from_plaintext(".OO\nOO.\n.O.".lines()).unwrap();
+        let mut grid = Grid::from_pattern(&r, 20);
+        let start = grid.cells().to_bitvec();
+
+        let mut history = History::new(&grid, Limits::default());
+        for _ in 0..30 {
+            grid.step(&life);
+            history.record(&grid);
+        }
+        assert_eq!(history.len(), 30);
+
+        while history.undo(&mut grid) {}
+        assert_eq!(grid.cells(), start.as_bitslice());
+        assert_eq!(grid.generation(), 0);
+
+        // the grid still steps correctly after going back
+        grid.step(&life);
+        assert_eq!(grid.population(), 6);
+    }
+
+    #[test]
+    fn edits_are_undone() {
+        let mut grid = Grid::new((16, 16));
+        let mut history = History::new(&grid, Limits::default());
+
+        grid.set((3, 4), true);
+        history.record(&grid);
+        grid.cells_mut().set_all(true);
+
+        // the fill was never recorded, so undoing records it first
+        assert!(history.undo(&mut grid));
+        assert_eq!(grid.population(), 1);
+        assert!(history.undo(&mut grid));
+        assert_eq!(grid.population(), 0);
+        assert!(!history.undo(&mut grid));
+    }
+
+    #[test]
+    fn oldest_entries_are_dropped() {
+        let mut grid = Grid::new((8, 8));
+        let limits = Limits {
+            max_entries: 3,
+            ..Limits::default()
+        };
+        let mut history = History::new(&grid, limits);
+
+        for x in 0..5 {
+            grid.set((x, 0), true);
+            history.record(&grid);
+        }
+        assert_eq!(history.len(), 3);
+
+        while history.undo(&mut grid) {}
+        assert_eq!(grid.population(), 2);
+
+        grid.resize((4, 4));
+        history.record(&grid);
+        assert!(history.is_empty());
+    }
+}
@@ -0,0 +1,716 @@
+//! Pictures of a region of a [`Grid`], as PNG images of one generation or
+//! animated GIFs of several, for documentation and reviews, and patterns
+//! read back from pictures.
+//!
+//! Both formats store palette indices rather than colors, since there are
+//! only ever three colors: dead cells, live cells and grid lines. The GIF
+//! encoder is written here, while PNGs are compressed by the `png` crate.
+
+use std::collections::HashMap;
+
+use crate::{grid::Grid, pattern::Pattern};
+
+/// How cells are drawn.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Style {
+    /// Pixels along each side of a cell.
+    pub cell_size: usize,
+    pub alive: [u8; 3],
+    pub dead: [u8; 3],
+    /// The color of lines between cells, if they're drawn. Each line takes
+    /// the first row or column of a cell's pixels, and one more row and
+    /// column close off the far edges, so cells need to be at least two
+    /// pixels wide.
+    pub grid_lines: Option<[u8; 3]>,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Style {
+            cell_size: 4,
+            alive: [255, 255, 255],
+            dead: [0, 0, 0],
+            grid_lines: None,
+        }
+    }
+}
+
+const DEAD: u8 = 0;
+const ALIVE: u8 = 1;
+const LINE: u8 = 2;
+
+/// The widest or tallest picture, which is the most PNGs allow.
+const MAX_SIDE: usize = i32::MAX as usize;
+/// The most pixels in a picture, each of which takes a byte while it's drawn.
+const MAX_PIXELS: usize = 1 << 28;
+
+impl Style {
+    /// The size in pixels of a picture of `size` cells, or `None` if it's
+    /// too large to count.
+    pub fn image_size(&self, size: (usize, usize)) -> Option<(usize, usize)> {
+        let edge = self.grid_lines.is_some() as usize;
+        let side = |cells: usize| cells.checked_mul(self.cell_size)?.checked_add(edge);
+        Some((side(size.0)?, side(size.1)?))
+    }
+
+    /// Dead, alive and line colors, in the order of their indices.
+    fn palette(&self) -> [u8; 9] {
+        let line = self.grid_lines.unwrap_or(self.dead);
+        let mut palette = [0; 9];
+        palette[..3].copy_from_slice(&self.dead);
+        palette[3..6].copy_from_slice(&self.alive);
+        palette[6..].copy_from_slice(&line);
+        palette
+    }
+
+    /// Checks that a picture of `size` cells can be drawn, returning its size
+    /// in pixels.
+    fn check(&self, size: (usize, usize)) -> Result<(usize, usize), &'static str> {
+        if self.cell_size == 0 {
+            return Err("cells must be at least one pixel wide");
+        }
+        if self.grid_lines.is_some() && self.cell_size < 2 {
+            return Err("cells must be at least two pixels wide to draw grid lines");
+        }
+        if size.0 == 0 || size.1 == 0 {
+            return Err("region is empty");
+        }
+        match self.image_size(size) {
+            Some((width, height))
+                if width <= MAX_SIDE
+                    && height <= MAX_SIDE
+                    && matches!(width.checked_mul(height), Some(area) if area <= MAX_PIXELS) =>
+            {
+                Ok((width, height))
+            }
+            _ => Err("image would be too large"),
+        }
+    }
+
+    /// The palette index of every pixel of the region, row by row.
+    fn rasterize(
+        &self,
+        grid: &Grid,
+        position: (usize, usize),
+        size: (usize, usize),
+    ) -> Result<Vec<u8>, &'static str> {
+        let (width, height) = self.check(size)?;
+        let world = grid.size();
+        if position.0 + size.0 > world.0 || position.1 + size.1 > world.1 {
+            return Err("region lies outside the world");
+        }
+
+        let lines = self.grid_lines.is_some();
+        let mut pixels = vec![DEAD; width * height];
+        for (y, row) in pixels.chunks_mut(width).enumerate() {
+            let (cell_y, inner_y) = (y / self.cell_size, y % self.cell_size);
+            for (x, pixel) in row.iter_mut().enumerate() {
+                let (cell_x, inner_x) = (x / self.cell_size, x % self.cell_size);
+                *pixel = if lines && (inner_x == 0 || inner_y == 0) {
+                    LINE
+                } else if cell_x < size.0
+                    && cell_y < size.1
+                    && grid.get((position.0 + cell_x, position.1 + cell_y))
+                {
+                    ALIVE
+                } else {
+                    DEAD
+                };
+            }
+        }
+
+        Ok(pixels)
+    }
+}
+
+/// Draws `size` cells of the grid, starting at `position`, as a PNG image.
+pub fn png(
+    grid: &Grid,
+    position: (usize, usize),
+    size: (usize, usize),
+    style: &Style,
+) -> Result<Vec<u8>, &'static str> {
+    let (width, height) = style.check(size)?;
+    let pixels = style.rasterize(grid, position, size)?;
+
+    let mut bytes = Vec::new();
+    let mut encoder = png::Encoder::new(&mut bytes, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Indexed);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_palette(style.palette().to_vec());
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(&pixels))
+        .map_err(|_| "could not encode PNG")?;
+
+    Ok(bytes)
+}
+
+/// An animated GIF being recorded one generation at a time. Every frame
+/// shows the same region, and is shown for the same time.
+#[derive(Clone, Debug)]
+pub struct Gif {
+    bytes: Vec<u8>,
+    style: Style,
+    position: (usize, usize),
+    size: (usize, usize),
+    /// How long each frame is shown, in hundredths of a second.
+    delay: u16,
+    frames: usize,
+}
+
+impl Gif {
+    /// Starts an animation of `size` cells of the world starting at
+    /// `position`. GIFs can't be more than 65535 pixels on a side, and most
+    /// viewers show frames for at least two hundredths of a second.
+    pub fn new(
+        position: (usize, usize),
+        size: (usize, usize),
+        style: &Style,
+        delay: u16,
+    ) -> Result<Self, &'static str> {
+        let (width, height) = style.check(size)?;
+        if width > u16::MAX as usize || height > u16::MAX as usize {
+            return Err("GIF would be too large");
+        }
+
+        let mut bytes = b"GIF89a".to_vec();
+        bytes.extend_from_slice(&(width as u16).to_le_bytes());
+        bytes.extend_from_slice(&(height as u16).to_le_bytes());
+        // a global palette of four colors, with the background dead
+        bytes.extend_from_slice(&[0b1001_0001, DEAD, 0]);
+        bytes.extend_from_slice(&style.palette());
+        bytes.extend_from_slice(&style.dead);
+        // the application extension that makes the animation loop forever
+        bytes.extend_from_slice(b"\x21\xff\x0bNETSCAPE2.0\x03\x01\x00\x00\x00");
+
+        Ok(Gif {
+            bytes,
+            style: *style,
+            position,
+            size,
+            delay,
+            frames: 0,
+        })
+    }
+
+    /// How many frames have been added.
+    pub fn frames(&self) -> usize {
+        self.frames
+    }
+
+    /// Adds the grid's current state as the next frame.
+    pub fn add_frame(&mut self, grid: &Grid) -> Result<(), &'static str> {
+        let (width, height) = self.style.check(self.size)?;
+        let pixels = self.style.rasterize(grid, self.position, self.size)?;
+
+        // the delay, then the image covering the whole animation
+        self.bytes.extend_from_slice(&[0x21, 0xf9, 0x04, 0]);
+        self.bytes.extend_from_slice(&self.delay.to_le_bytes());
+        self.bytes.extend_from_slice(&[0, 0]);
+        self.bytes.extend_from_slice(&[0x2c, 0, 0, 0, 0]);
+        self.bytes.extend_from_slice(&(width as u16).to_le_bytes());
+        self.bytes.extend_from_slice(&(height as u16).to_le_bytes());
+        self.bytes.push(0);
+
+        self.bytes.push(MIN_CODE_SIZE);
+        for block in lzw(&pixels).chunks(255) {
+            self.bytes.push(block.len() as u8);
+            self.bytes.extend_from_slice(block);
+        }
+        self.bytes.push(0);
+
+        self.frames += 1;
+        Ok(())
+    }
+
+    /// Ends the animation, returning the file's contents.
+    pub fn finish(mut self) -> Vec<u8> {
+        self.bytes.push(0x3b);
+        self.bytes
+    }
+}
+
+/// Records `frames` generations of the grid, from its current state on,
+/// stepping it between frames.
+pub fn gif<A: crate::Automata>(
+    grid: &mut Grid,
+    machine: &A,
+    frames: usize,
+    position: (usize, usize),
+    size: (usize, usize),
+    style: &Style,
+    delay: u16,
+) -> Result<Vec<u8>, &'static str> {
+    let mut gif = Gif::new(position, size, style, delay)?;
+    for frame in 0..frames {
+        if frame > 0 {
+            grid.step(machine);
+        }
+        gif.add_frame(grid)?;
+    }
+    Ok(gif.finish())
+}
+
+/// How gray levels are turned into live and dead cells when a picture is
+/// imported.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Dither {
+    /// Each cell is compared with the threshold on its own, which keeps
+    /// edges sharp but loses shading.
+    Threshold,
+    /// Floyd–Steinberg error diffusion: what each cell gains or loses by
+    /// rounding is passed on to its neighbours to the right and below, so
+    /// shades come out as scattered cells of the same density.
+    FloydSteinberg,
+    /// Cells are compared with a repeating 4x4 Bayer matrix of thresholds,
+    /// so shades come out as regular textures.
+    Ordered,
+}
+
+/// How a picture is turned into a pattern.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Import {
+    /// Cells per pixel, so 0.25 makes each cell from four by four pixels
+    /// and 2 makes two by two cells from each pixel.
+    pub scale: f32,
+    pub dither: Dither,
+    /// The gray level, from 0 for black to 255 for white, that dark pixels
+    /// are below.
+    pub threshold: u8,
+    /// Dark pixels become live cells, unless this is set, when light ones
+    /// do. Transparent pixels count as white either way.
+    pub invert: bool,
+}
+
+impl Default for Import {
+    fn default() -> Self {
+        Import {
+            scale: 1.,
+            dither: Dither::Threshold,
+            threshold: 128,
+            invert: false,
+        }
+    }
+}
+
+/// The 4x4 Bayer matrix, whose entries spread the sixteen levels of a cell
+/// as evenly as possible over each block.
+const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// Reads a PNG image into a pattern.
+pub fn import_png(bytes: &[u8], import: &Import) -> Result<Pattern, &'static str> {
+    let (gray, size) = decode_gray(bytes)?;
+    import.convert(&gray, size)
+}
+
+impl Import {
+    /// Turns gray levels, row by row, into a pattern.
+    pub fn convert(&self, gray: &[f32], size: (usize, usize)) -> Result<Pattern, &'static str> {
+        if !(self.scale.is_finite() && self.scale > 0.) {
+            return Err("scale must be a positive number");
+        }
+        if size.0 == 0 || size.1 == 0 {
+            return Err("image is empty");
+        }
+        if gray.len() < size.0 * size.1 {
+            return Err("image has fewer pixels than its size");
+        }
+
+        let cells = |pixels: usize| ((pixels as f32 * self.scale).round() as usize).max(1);
+        let (width, height) = (cells(size.0), cells(size.1));
+        if !matches!(width.checked_mul(height), Some(area) if area <= MAX_IMPORT) {
+            return Err("image is too large at this scale");
+        }
+
+        // each cell averages the pixels it covers, or repeats the one it's in
+        let span = |cell: usize, cells: usize, pixels: usize| {
+            let start = cell * pixels / cells;
+            start..((cell + 1) * pixels / cells).max(start + 1)
+        };
+        let mut levels = Vec::with_capacity(width * height);
+        for y in 0..height {
+            let rows = span(y, height, size.1);
+            for x in 0..width {
+                let columns = span(x, width, size.0);
+                let mut total = 0.;
+                for row in rows.clone() {
+                    let start = row * size.0;
+                    total += gray[start + columns.start..start + columns.end]
+                        .iter()
+                        .sum::<f32>();
+                }
+                levels.push(total / (rows.len() * columns.len()) as f32);
+            }
+        }
+
+        let threshold = self.threshold as f32;
+        let mut pattern = Pattern::new((width, height));
+        for y in 0..height {
+            for x in 0..width {
+                let level = levels[y * width + x];
+                let dark = match self.dither {
+                    Dither::Threshold => level < threshold,
+                    Dither::FloydSteinberg => {
+                        let dark = level < threshold;
+                        let error = level - if dark { 0. } else { 255. };
+                        let mut spread = |dx: isize, dy: usize, share: f32| {
+                            let x = x as isize + dx;
+                            if x >= 0 && (x as usize) < width && y + dy < height {
+                                levels[(y + dy) * width + x as usize] += error * share;
+                            }
+                        };
+                        spread(1, 0, 7. / 16.);
+                        spread(-1, 1, 3. / 16.);
+                        spread(0, 1, 5. / 16.);
+                        spread(1, 1, 1. / 16.);
+                        dark
+                    }
+                    Dither::Ordered => {
+                        let offset = (BAYER[y % 4][x % 4] as f32 + 0.5) / 16. - 0.5;
+                        level + offset * 255. < threshold
+                    }
+                };
+                pattern.set((x, y), dark != self.invert);
+            }
+        }
+        Ok(pattern)
+    }
+}
+
+/// The most cells an imported pattern may have.
+const MAX_IMPORT: usize = 1 << 24;
+
+/// Decodes a PNG image of any color type into gray levels, row by row, with
+/// transparent pixels blended into white.
+fn decode_gray(bytes: &[u8]) -> Result<(Vec<f32>, (usize, usize)), &'static str> {
+    let mut decoder = png::Decoder::new(bytes);
+    // palettes and low bit depths are expanded to eight bit samples
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+    let (info, mut reader) = decoder.read_info().map_err(|_| "could not decode PNG")?;
+    let mut buffer = vec![0; info.buffer_size()];
+    reader
+        .next_frame(&mut buffer)
+        .map_err(|_| "could not decode PNG")?;
+
+    let channels = info.color_type.samples();
+    let size = (info.width as usize, info.height as usize);
+    let mut gray = Vec::with_capacity(size.0 * size.1);
+    for row in buffer.chunks(info.line_size).take(size.1) {
+        for pixel in row.chunks(channels).take(size.0) {
+            let (level, alpha) = match *pixel {
+                [level] => (level as f32, 255.),
+                [level, alpha] => (level as f32, alpha as f32),
+                [red, green, blue] => (luma(red, green, blue), 255.),
+                [red, green, blue, alpha] => (luma(red, green, blue), alpha as f32),
+                _ => return Err("could not decode PNG"),
+            };
+            let alpha = alpha / 255.;
+            gray.push(level * alpha + 255. * (1. - alpha));
+        }
+    }
+    Ok((gray, size))
+}
+
+/// The brightness of a color as people see it, from the weights of Rec. 601.
+fn luma(red: u8, green: u8, blue: u8) -> f32 {
+    0.299 * red as f32 + 0.587 * green as f32 + 0.114 * blue as f32
+}
+
+/// The bits per pixel of the palette, which LZW codes start one wider than.
+const MIN_CODE_SIZE: u8 = 2;
+/// GIF's LZW codes are at most twelve bits wide.
+const MAX_CODES: u16 = 4096;
+
+/// Compresses palette indices with the variant of LZW that GIF uses: codes
+/// start one bit wider than the indices and grow as the table fills, and
+/// are packed starting from the lowest bit.
+fn lzw(indices: &[u8]) -> Vec<u8> {
+    let clear = 1u16 << MIN_CODE_SIZE;
+    let end = clear + 1;
+
+    let mut output = Vec::new();
+    let (mut buffer, mut bits) = (0u32, 0);
+    let mut emit = |code: u16, size: u32, output: &mut Vec<u8>| {
+        buffer |= (code as u32) << bits;
+        bits += size;
+        while bits >= 8 {
+            output.push(buffer as u8);
+            buffer >>= 8;
+            bits -= 8;
+        }
+    };
+
+    let mut table: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut next = end + 1;
+    let mut size = MIN_CODE_SIZE as u32 + 1;
+    emit(clear, size, &mut output);
+
+    let mut indices = indices.iter();
+    let mut prefix = match indices.next() {
+        Some(&first) => first as u16,
+        None => {
+            emit(end, size, &mut output);
+            emit(0, 7, &mut output);
+            return output;
+        }
+    };
+
+    for &index in indices {
+        if let Some(&code) = table.get(&(prefix, index)) {
+            prefix = code;
+            continue;
+        }
+
+        emit(prefix, size, &mut output);
+        if next < MAX_CODES {
+            table.insert((prefix, index), next);
+            next += 1;
+            // the decoder adds each code one step later, so widen once the
+            // code it will add next no longer fits
+            if next > (1 << size) && size < 12 {
+                size += 1;
+            }
+        } else {
+            emit(clear, size, &mut output);
+            table.clear();
+            next = end + 1;
+            size = MIN_CODE_SIZE as u32 + 1;
+        }
+        prefix = index as u16;
+    }
+
+    emit(prefix, size, &mut output);
+    // reading the last code adds the decoder's last entry, which can widen
+    // the end code
+    if next == (1 << size) && size < 12 {
+        size += 1;
+    }
+    emit(end, size, &mut output);
+    // flush the last partial byte
+    emit(0, 7, &mut output);
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::LifeLike;
+
+    /// Undoes [`lzw`], the way GIF decoders do.
+    fn unlzw(bytes: &[u8]) -> Vec<u8> {
+        let clear = 1u16 << MIN_CODE_SIZE;
+        let end = clear + 1;
+        let mut table: Vec<Vec<u8>> = Vec::new();
+        let mut size = MIN_CODE_SIZE as u32 + 1;
+        let mut previous: Option<Vec<u8>> = None;
+        let mut output = Vec::new();
+
+        let (mut buffer, mut bits) = (0u32, 0);
+        let mut bytes = bytes.iter();
+        loop {
+            while bits < size {
+                buffer |= (*bytes.next().expect("ran out of codes") as u32) << bits;
+                bits += 8;
+            }
+            let code = (buffer & ((1 << size) - 1)) as u16;
+            buffer >>= size;
+            bits -= size;
+
+            if code == clear {
+                table = (0..clear).map(|index| vec![index as u8]).collect();
+                table.push(Vec::new());
+                table.push(Vec::new());
+                size = MIN_CODE_SIZE as u32 + 1;
+                previous = None;
+                continue;
+            }
+            if code == end {
+                return output;
+            }
+
+            let entry = match (table.get(code as usize), &previous) {
+                (Some(entry), _) => entry.clone(),
+                (None, Some(previous)) => {
+                    let mut entry = previous.clone();
+                    entry.push(previous[0]);
+                    entry
+                }
+                (None, None) => panic!("code {} isn't in the table", code),
+            };
+            if let Some(mut previous) = previous.take() {
+                if table.len() < MAX_CODES as usize {
+                    previous.push(entry[0]);
+                    table.push(previous);
+                    if table.len() == (1 << size) && size < 12 {
+                        size += 1;
+                    }
+                }
+            }
+            output.extend_from_slice(&entry);
+            previous = Some(entry);
+        }
+    }
+
+    #[test]
+    fn gif_frames() {
+        let life = LifeLike::new("B3/S23").unwrap();
+        let glider = Pattern::from_plaintext(".O.\n..O\nOOO".lines()).unwrap();
+        let mut grid = Grid::from_pattern(&glider, 2);
+        let style = Style {
+            cell_size: 3,
+            grid_lines: Some([60, 60, 60]),
+            ..Style::default()
+        };
+
+        let bytes = gif(&mut grid, &life, 4, (0, 0), (7, 7), &style, 10).unwrap();
+        assert!(bytes.starts_with(b"GIF89a"));
+        assert_eq!(&bytes[6..10], &[22, 0, 22, 0]);
+        assert_eq!(bytes.last(), Some(&0x3b));
+        assert_eq!(grid.generation(), 3);
+
+        assert!(Gif::new(
+            (0, 0),
+            (7, 7),
+            &Style {
+                cell_size: 1,
+                ..style
+            },
+            10
+        )
+        .is_err());
+        let mut outside = Gif::new((4, 4), (7, 7), &Style::default(), 10).unwrap();
+        assert!(outside.add_frame(&grid).is_err());
+    }
+
+    #[test]
+    fn png_image() {
+        let block = Pattern::from_plaintext("OO\nOO".lines()).unwrap();
+        let grid = Grid::from_pattern(&block, 1);
+
+        let bytes = png(&grid, (0, 0), (4, 4), &Style::default()).unwrap();
+        assert!(bytes.starts_with(b"\x89PNG\r\n\x1a\n"));
+        // the width and height in the header chunk
+        assert_eq!(&bytes[16..24], &[0, 0, 0, 16, 0, 0, 0, 16]);
+
+        // sizes that overflow, or that would take too much memory, are refused
+        let huge = Style {
+            cell_size: usize::MAX / 2,
+            ..Style::default()
+        };
+        assert!(png(&grid, (0, 0), (4, 4), &huge).is_err());
+        let large = Style {
+            cell_size: 1 << 15,
+            ..Style::default()
+        };
+        assert!(png(&grid, (0, 0), (4, 4), &large).is_err());
+        assert!(Gif::new((0, 0), (4, 4), &large, 10).is_err());
+    }
+
+    #[test]
+    fn pixels() {
+        let mut grid = Grid::new((2, 1));
+        grid.set((1, 0), true);
+        let style = Style {
+            cell_size: 2,
+            grid_lines: Some([1, 2, 3]),
+            ..Style::default()
+        };
+
+        let pixels = style.rasterize(&grid, (0, 0), (2, 1)).unwrap();
+        #[rustfmt::skip]
+        assert_eq!(pixels, vec![
+            LINE, LINE, LINE, LINE, LINE,
+            LINE, DEAD, LINE, ALIVE, LINE,
+            LINE, LINE, LINE, LINE, LINE,
+        ]);
+    }
+
+    #[test]
+    fn lzw_fills_the_table() {
+        // long enough that the table fills and is cleared a few times
+        let indices: Vec<u8> = (0..200_000u32)
+            .map(|i| ((i * 7 + i / 13) % 5 % 3) as u8)
+            .collect();
+        assert_eq!(unlzw(&lzw(&indices)), indices);
+        assert_eq!(unlzw(&lzw(&[])), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn png_round_trip() {
+        let glider = Pattern::from_plaintext(".O.\n..O\nOOO".lines()).unwrap();
+        let grid = Grid::from_pattern(&glider, 1);
+        let bytes = png(&grid, (0, 0), (5, 5), &Style::default()).unwrap();
+
+        // the picture is white on black, so light pixels are the live ones
+        let import = Import {
+            scale: 0.25,
+            invert: true,
+            ..Import::default()
+        };
+        let pattern = import_png(&bytes, &import).unwrap();
+        assert_eq!(pattern.size(), (5, 5));
+        for y in 0..5 {
+            for x in 0..5 {
+                assert_eq!(pattern.get((x, y)), grid.get((x, y)));
+            }
+        }
+
+        let doubled = import_png(
+            &bytes,
+            &Import {
+                scale: 0.5,
+                ..import
+            },
+        )
+        .unwrap();
+        assert_eq!(doubled.size(), (10, 10));
+        assert_eq!(doubled.population(), 4 * glider.population());
+
+        assert!(import_png(b"not a picture", &import).is_err());
+        assert!(import_png(
+            &bytes,
+            &Import {
+                scale: 0.,
+                ..import
+            }
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn dithering() {
+        // a quarter of the way from white to black
+        let gray = vec![192.; 32 * 32];
+        let with = |dither| {
+            Import {
+                dither,
+                ..Import::default()
+            }
+            .convert(&gray, (32, 32))
+            .unwrap()
+        };
+
+        assert_eq!(with(Dither::Threshold).population(), 0);
+        let ordered = with(Dither::Ordered);
+        assert_eq!(ordered.population(), 256);
+        for block in 0..8 {
+            let cells = (0..4)
+                .flat_map(|y| (0..4).map(move |x| (4 * block + x, y)))
+                .filter(|&cell| ordered.get(cell))
+                .count();
+            assert_eq!(cells, 4);
+        }
+        let diffused = with(Dither::FloydSteinberg).population();
+        assert!((240..=272).contains(&diffused), "{} cells", diffused);
+    }
+
+    proptest! {
+        #[test]
+        fn pt_lzw_round_trips(indices in prop::collection::vec(0u8..3, 0..5000)) {
+            prop_assert_eq!(unlzw(&lzw(&indices)), indices);
+        }
+    }
+}
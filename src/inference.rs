@@ -0,0 +1,163 @@
+//! Works out which Life-like rules could have produced a sequence of
+//! generations.
+//!
+//! Every cell of every snapshot but the last is a sample of the rule: its
+//! status and live neighbor count pick a transition, the same way
+//! [`LifeLike`] indexes its rule table, and the cell's status in the following
+//! snapshot shows what that transition did.
+
+use bitvec::prelude::*;
+
+use crate::{moore_neighborhood_wrapping, LifeLike};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Transition {
+    /// No cell ever had this status and neighbor count.
+    Unobserved,
+    /// Every sample agrees on the outcome.
+    Forced(bool),
+    /// Samples disagree, so no Life-like rule explains the snapshots.
+    Contradicted,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Inference {
+    transitions: [Transition; 18],
+}
+
+impl Inference {
+    /// What the snapshots say about the transition for a cell with the given
+    /// status and number of live neighbors.
+    pub fn transition(&self, status: bool, neighbors: usize) -> Transition {
+        self.transitions[LifeLike::encode_index(status, neighbors)]
+    }
+
+    /// Whether at least one rule is compatible with the snapshots.
+    pub fn is_consistent(&self) -> bool {
+        !self.transitions.contains(&Transition::Contradicted)
+    }
+
+    /// Every rulestring that reproduces the snapshots. Each unobserved
+    /// transition doubles the number of rules, so this can be large when the
+    /// snapshots are small.
+    pub fn compatible_rules(&self) -> Vec<String> {
+        if !self.is_consistent() {
+            return Vec::new();
+        }
+
+        let unobserved: Vec<usize> = (0..=8)
+            .flat_map(|neighbors| {
+                vec![
+                    LifeLike::encode_index(false, neighbors),
+                    LifeLike::encode_index(true, neighbors),
+                ]
+            })
+            .filter(|&index| self.transitions[index] == Transition::Unobserved)
+            .collect();
+
+        let mut base = [false; 18];
+        for (rule, transition) in base.iter_mut().zip(self.transitions.iter()) {
+            *rule = *transition == Transition::Forced(true);
+        }
+
+        (0..(1usize << unobserved.len()))
+            .map(|choice| {
+                let mut rules = base;
+                for (bit, &index) in unobserved.iter().enumerate() {
+                    rules[index] = choice & (1 << bit) != 0;
+                }
+                LifeLike::from_rules(rules).to_string()
+            })
+            .collect()
+    }
+}
+
+/// Infers the rule from consecutive snapshots of a world of the given size,
+/// which wraps around at the edges like [`LifeLike::update`] does.
+pub fn infer(
+    snapshots: &[&BitSlice<Lsb0, usize>],
+    size: (usize, usize),
+) -> Result<Inference, &'static str> {
+    if snapshots.len() < 2 {
+        return Err("need at least two snapshots to infer a rule");
+    }
+
+    if snapshots
+        .iter()
+        .any(|snapshot| snapshot.len() != size.0 * size.1)
+    {
+        return Err("snapshot does not match the given size");
+    }
+
+    let mut transitions = [Transition::Unobserved; 18];
+
+    for pair in snapshots.windows(2) {
+        let (before, after) = (pair[0], pair[1]);
+
+        for index in 0..before.len() {
+            let neighbors = moore_neighborhood_wrapping((index % size.0, index / size.0), size)
+                .iter()
+                .filter(|(x, y)| before[x + (y * size.0)])
+                .count();
+
+            let transition = &mut transitions[LifeLike::encode_index(before[index], neighbors)];
+            *transition = match *transition {
+                Transition::Unobserved => Transition::Forced(after[index]),
+                Transition::Forced(outcome) if outcome == after[index] => *transition,
+                _ => Transition::Contradicted,
+            };
+        }
+    }
+
+    Ok(Inference { transitions })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{grid::Grid, pattern::Pattern};
+
+    #[test]
+    fn infers_life_from_r_pentomino() {
+        let life = LifeLike::new("B3/S23").unwrap();
+        let r = Pattern::from_plaintext(".OO\nOO.\n.O.".lines()).unwrap();
+        let mut grid = Grid::from_pattern(&r, 20);
+
+        let mut snapshots = vec![grid.cells().to_bitvec()];
+        for _ in 0..50 {
+            grid.step(&life);
+            snapshots.push(grid.cells().to_bitvec());
+        }
+        let snapshots: Vec<&BitSlice<Lsb0, usize>> =
+            snapshots.iter().map(|s| s.as_bitslice()).collect();
+
+        let inference = infer(&snapshots, grid.size()).unwrap();
+        assert!(inference.is_consistent());
+        assert_eq!(inference.transition(false, 3), Transition::Forced(true));
+        assert_eq!(inference.transition(true, 2), Transition::Forced(true));
+        assert_eq!(inference.transition(true, 1), Transition::Forced(false));
+        assert_eq!(inference.transition(false, 0), Transition::Forced(false));
+
+        let rules = inference.compatible_rules();
+        assert!(rules.contains(&"B3/S23".to_owned()));
+        assert!(rules
+            .iter()
+            .all(|rule| LifeLike::new(rule).unwrap().simulate(false, 3)));
+    }
+
+    #[test]
+    fn detects_contradictions() {
+        let size = (4, 4);
+        let empty: BitVec<Lsb0, usize> = BitVec::repeat(false, 16);
+        let mut spark = empty.clone();
+        spark.set(5, true);
+
+        let inference = infer(&[&empty, &spark], size).unwrap();
+        assert_eq!(inference.transition(false, 0), Transition::Contradicted);
+        assert_eq!(inference.transition(true, 0), Transition::Unobserved);
+        assert!(inference.compatible_rules().is_empty());
+
+        assert!(infer(&[&empty], size).is_err());
+        assert!(infer(&[&empty, &spark], (3, 3)).is_err());
+    }
+}
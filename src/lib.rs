@@ -1,8 +1,23 @@
 #![allow(dead_code)]
 
+use std::fmt;
+
 use bitvec::prelude::*;
 
+pub mod age;
+pub mod cli;
+pub mod enumerate;
+pub mod grid;
+pub mod history;
+pub mod image;
+pub mod inference;
 pub mod pattern;
+pub mod predecessor;
+pub mod range;
+pub mod snapshot;
+pub mod speed;
+pub mod stability;
+pub mod text;
 
 pub trait Automata {
     fn update<O: BitOrder, T: BitStore>(
@@ -14,6 +29,7 @@ pub trait Automata {
     );
 }
 
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct LifeLike {
     rules: Box<[bool; 18]>,
 }
@@ -49,6 +65,7 @@ impl LifeLike {
         for c in b.chars() {
             let neighbors = c
                 .to_digit(10)
+                .filter(|&neighbors| neighbors <= 8)
                 .ok_or("could not parse numbers from definition string first part")?;
             rules[Self::encode_index(false, neighbors as usize)] = true;
         }
@@ -56,6 +73,7 @@ impl LifeLike {
         for c in s.chars() {
             let neighbors = c
                 .to_digit(10)
+                .filter(|&neighbors| neighbors <= 8)
                 .ok_or("could not parse numbers from definition string second part")?;
             rules[Self::encode_index(true, neighbors as usize)] = true;
         }
@@ -65,11 +83,30 @@ impl LifeLike {
         })
     }
 
+    pub(crate) fn from_rules(rules: [bool; 18]) -> Self {
+        LifeLike {
+            rules: Box::new(rules),
+        }
+    }
+
     pub fn simulate(&self, status: bool, neighbors: usize) -> bool {
         self.rules[Self::encode_index(status, neighbors)]
     }
 }
 
+impl fmt::Display for LifeLike {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = |status| {
+            (0..=8)
+                .filter(|&neighbors| self.simulate(status, neighbors))
+                .map(|neighbors| char::from(b'0' + neighbors as u8))
+                .collect::<String>()
+        };
+
+        write!(f, "B{}/S{}", digits(false), digits(true))
+    }
+}
+
 impl Automata for LifeLike {
     fn update<O: BitOrder, T: BitStore>(
         &self,
@@ -220,6 +257,18 @@ mod test {
         let _life = LifeLike::new("B3/S23").unwrap();
     }
 
+    #[test]
+    fn rulestring_is_normalized() {
+        let highlife = LifeLike::new("b63/s32").unwrap();
+        assert_eq!(highlife.to_string(), "B36/S23");
+    }
+
+    #[test]
+    fn rulestring_rejects_nine_neighbors() {
+        assert!(LifeLike::new("B39/S23").is_err());
+        assert!(LifeLike::new("B3/S239").is_err());
+    }
+
     proptest! {
         #[test]
         fn pt_lifelike_compiles_valid_strings(rule in "B[0-8]{0,8}/S[0-8]{0,8}") {
@@ -227,6 +276,14 @@ mod test {
         }
     }
 
+    proptest! {
+        #[test]
+        fn pt_lifelike_display_round_trips(rule in "B[0-8]{0,8}/S[0-8]{0,8}") {
+            let life = LifeLike::new(&rule).unwrap();
+            prop_assert_eq!(LifeLike::new(&life.to_string()).unwrap(), life)
+        }
+    }
+
     proptest! {
         #[test]
         fn pt_lifelike_matches_logic(neighbors in 0usize..9) {
@@ -1,11 +1,154 @@
 use bitvec::prelude::*;
 
+use crate::LifeLike;
+
+/// The most cells a pattern read from RLE can have, so that a header can't
+/// ask for more memory than there is.
+pub const MAX_CELLS: usize = 1 << 28;
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Pattern {
     store: BitVec<Lsb0, usize>,
     size: (usize, usize),
 }
 
 impl Pattern {
+    /// Creates an empty pattern of the given size.
+    pub fn new(size: (usize, usize)) -> Self {
+        Pattern {
+            store: BitVec::repeat(false, size.0 * size.1),
+            size,
+        }
+    }
+
+    /// Copies the rectangle of `size` cells at `position` out of a world.
+    pub fn from_region(
+        world: &BitSlice<Lsb0, usize>,
+        world_size: (usize, usize),
+        position: (usize, usize),
+        size: (usize, usize),
+    ) -> Result<Self, &'static str> {
+        if world_size.0 * world_size.1 > world.len() {
+            return Err("world not big enough for given size");
+        }
+
+        if (position.0 + size.0) > world_size.0 || (position.1 + size.1) > world_size.1 {
+            return Err("region: region does not fit inside the world");
+        }
+
+        let mut store: BitVec<Lsb0, usize> = BitVec::with_capacity(size.0 * size.1);
+        for row in 0..size.1 {
+            let beginning = position.0 + (world_size.0 * (position.1 + row));
+            store.extend_from_bitslice(&world[beginning..(beginning + size.0)]);
+        }
+
+        Ok(Pattern { store, size })
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        self.size
+    }
+
+    pub fn get(&self, cell: (usize, usize)) -> bool {
+        self.store[cell.0 + (cell.1 * self.size.0)]
+    }
+
+    pub fn set(&mut self, cell: (usize, usize), value: bool) {
+        self.store.set(cell.0 + (cell.1 * self.size.0), value);
+    }
+
+    pub fn population(&self) -> usize {
+        self.store.count_ones()
+    }
+
+    /// Crops away empty rows and columns around the edges, returning the
+    /// position of the cropped pattern within this one. Empty patterns have
+    /// nothing to keep.
+    pub fn trimmed(&self) -> Option<((usize, usize), Pattern)> {
+        let (min, max) = bounding_box(&self.store, self.size)?;
+        let size = (max.0 - min.0 + 1, max.1 - min.1 + 1);
+        let trimmed = Pattern::from_region(&self.store, self.size, min, size)
+            .expect("bounding box lies inside the pattern");
+        Some((min, trimmed))
+    }
+
+    /// Visits every cell of the pattern and the ring of empty cells around it,
+    /// with its status and number of live neighbors. Positions are shifted by
+    /// one so that the ring starts at zero.
+    pub(crate) fn neighborhoods(&self) -> impl Iterator<Item = ((usize, usize), bool, usize)> + '_ {
+        let (width, height) = self.size;
+        let alive = move |x: isize, y: isize| {
+            (0..width as isize).contains(&x)
+                && (0..height as isize).contains(&y)
+                && self.get((x as usize, y as usize))
+        };
+
+        (0..(height + 2)).flat_map(move |y| {
+            (0..(width + 2)).map(move |x| {
+                let (cx, cy) = (x as isize - 1, y as isize - 1);
+                let neighbors = [
+                    (-1, -1),
+                    (0, -1),
+                    (1, -1),
+                    (1, 0),
+                    (1, 1),
+                    (0, 1),
+                    (-1, 1),
+                    (-1, 0),
+                ]
+                .iter()
+                .filter(|(dx, dy)| alive(cx + dx, cy + dy))
+                .count();
+                ((x, y), alive(cx, cy), neighbors)
+            })
+        })
+    }
+
+    /// Computes the next generation of the pattern surrounded by empty space.
+    /// The result is one cell larger than the pattern on every side.
+    pub fn evolve(&self, machine: &LifeLike) -> Pattern {
+        let mut next = Pattern::new((self.size.0 + 2, self.size.1 + 2));
+        for (cell, status, neighbors) in self.neighborhoods() {
+            next.set(cell, machine.simulate(status, neighbors));
+        }
+        next
+    }
+
+    /// Returns the pattern turned a quarter turn clockwise.
+    pub fn rotate_clockwise(&self) -> Self {
+        let mut rotated = Pattern::new((self.size.1, self.size.0));
+        for y in 0..self.size.1 {
+            for x in 0..self.size.0 {
+                rotated.set((self.size.1 - 1 - y, x), self.get((x, y)));
+            }
+        }
+        rotated
+    }
+
+    /// Returns the pattern mirrored left to right.
+    pub fn flip_horizontal(&self) -> Self {
+        let mut flipped = Pattern::new(self.size);
+        for y in 0..self.size.1 {
+            for x in 0..self.size.0 {
+                flipped.set((self.size.0 - 1 - x, y), self.get((x, y)));
+            }
+        }
+        flipped
+    }
+
+    /// All eight images of the pattern under rotation and reflection, starting
+    /// with the pattern itself. Symmetric patterns produce repeated entries.
+    pub fn symmetries(&self) -> [Pattern; 8] {
+        let r1 = self.rotate_clockwise();
+        let r2 = r1.rotate_clockwise();
+        let r3 = r2.rotate_clockwise();
+        let f0 = self.flip_horizontal();
+        let f1 = r1.flip_horizontal();
+        let f2 = r2.flip_horizontal();
+        let f3 = r3.flip_horizontal();
+        [self.clone(), r1, r2, r3, f0, f1, f2, f3]
+    }
+
     pub fn from_plaintext<'a>(lines: impl Iterator<Item = &'a str>) -> Result<Self, &'static str> {
         let mut store: BitVec<Lsb0, usize> = BitVec::new();
         let mut width = None;
@@ -36,6 +179,211 @@ impl Pattern {
         }
     }
 
+    /// Reads a pattern in the run length encoded format used by Golly and
+    /// LifeViewer. A rule in the header is ignored, and every state other than
+    /// `b` (or `.`) counts as alive.
+    pub fn from_rle(text: &str) -> Result<Self, &'static str> {
+        let mut lines = text
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#'));
+
+        let header = lines.next().ok_or("no RLE header found")?;
+        let mut size: (Option<usize>, Option<usize>) = (None, None);
+        for field in header.split(',') {
+            let mut parts = field.splitn(2, '=').map(|part| part.trim());
+            match (parts.next(), parts.next().map(|value| value.parse())) {
+                (Some("x"), Some(Ok(x))) => size.0 = Some(x),
+                (Some("y"), Some(Ok(y))) => size.1 = Some(y),
+                (Some("x" | "y"), _) => return Err("RLE header has an invalid size"),
+                _ => {}
+            }
+        }
+
+        let size = match size {
+            (Some(x), Some(y)) => (x, y),
+            _ => return Err("RLE header is missing the size"),
+        };
+        if !matches!(size.0.checked_mul(size.1), Some(cells) if cells <= MAX_CELLS) {
+            return Err("RLE header size is too large");
+        }
+
+        let mut pattern = Pattern::new(size);
+        let (mut x, mut y): (usize, usize) = (0, 0);
+        let mut run: Option<usize> = None;
+
+        'lines: for line in lines {
+            for c in line.chars() {
+                match c {
+                    '0'..='9' => {
+                        let digit = c as usize - '0' as usize;
+                        run = run
+                            .unwrap_or(0)
+                            .checked_mul(10)
+                            .and_then(|run| run.checked_add(digit));
+                        if run.is_none() {
+                            return Err("RLE run is too long");
+                        }
+                    }
+                    '$' => {
+                        y = y.saturating_add(run.take().unwrap_or(1));
+                        x = 0;
+                    }
+                    '!' => break 'lines,
+                    c if c.is_ascii_alphabetic() || c == '.' => {
+                        let count = run.take().unwrap_or(1);
+                        if c != 'b' && c != '.' {
+                            if x.saturating_add(count) > size.0 || y >= size.1 {
+                                return Err("RLE cells lie outside the size in the header");
+                            }
+                            for offset in 0..count {
+                                pattern.set((x + offset, y), true);
+                            }
+                        }
+                        x = x.saturating_add(count);
+                    }
+                    c if c.is_whitespace() => {}
+                    _ => return Err("unexpected character in RLE"),
+                }
+            }
+        }
+
+        Ok(pattern)
+    }
+
+    /// Writes the pattern as run length encoded text, naming `rule` in the
+    /// header if one is given.
+    pub fn to_rle(&self, rule: Option<&LifeLike>) -> String {
+        let mut rle = format!("x = {}, y = {}", self.size.0, self.size.1);
+        if let Some(rule) = rule {
+            rle.push_str(&format!(", rule = {}", rule));
+        }
+        rle.push('\n');
+
+        // runs of `b` (dead), `o` (alive) and `$` (end of row), leaving out
+        // dead cells at the end of rows and empty rows at the end
+        let mut runs: Vec<(usize, char)> = Vec::new();
+        let mut rows = 0;
+        for y in 0..self.size.1 {
+            let row = &self.store[(y * self.size.0)..((y + 1) * self.size.0)];
+            if row.any() {
+                if rows > 0 {
+                    runs.push((rows, '$'));
+                    rows = 0;
+                }
+
+                let end = row.last_one().map_or(0, |last| last + 1);
+                for alive in row[..end].iter().by_val() {
+                    let tag = if alive { 'o' } else { 'b' };
+                    match runs.last_mut() {
+                        Some((count, last)) if *last == tag => *count += 1,
+                        _ => runs.push((1, tag)),
+                    }
+                }
+            }
+            rows += 1;
+        }
+        runs.push((1, '!'));
+
+        // lines are kept under 70 characters, as the format asks
+        let mut line = String::new();
+        for (count, tag) in runs {
+            let item = match count {
+                1 => tag.to_string(),
+                _ => format!("{}{}", count, tag),
+            };
+            if line.len() + item.len() > 70 {
+                rle.push_str(&line);
+                rle.push('\n');
+                line.clear();
+            }
+            line.push_str(&item);
+        }
+        rle.push_str(&line);
+        rle.push('\n');
+
+        rle
+    }
+
+    /// Reads a pattern from the contents of a file called `name`, either
+    /// plaintext (`.cells`) or RLE (`.rle`). Without one of those extensions,
+    /// the format is guessed from the text: RLE starts with a header like
+    /// `x = 3, y = 3` after any `#` comments.
+    pub fn from_file(name: &str, text: &str) -> Result<Self, &'static str> {
+        let extension = name.rsplit_once('.').map(|(_, extension)| extension);
+        let rle = match extension {
+            Some(extension) if extension.eq_ignore_ascii_case("rle") => true,
+            Some(extension) if extension.eq_ignore_ascii_case("cells") => false,
+            _ => text
+                .lines()
+                .map(|line| line.trim())
+                .find(|line| !line.is_empty() && !line.starts_with('#'))
+                .is_some_and(|line| line.starts_with('x')),
+        };
+
+        if rle {
+            Pattern::from_rle(text)
+        } else {
+            Pattern::from_plaintext(text.lines())
+        }
+    }
+
+    /// Reads a pattern from an apgcode, the names Catagolue gives objects,
+    /// like `xs4_33` for the block or `xq4_153` for a glider. The part before
+    /// the underscore is optional, and only the cells are read from the rest.
+    ///
+    /// Cells are encoded in strips of five rows separated by `z`, one
+    /// character per column in base 32 with the top row as the lowest bit.
+    /// `w` and `x` stand for two and three empty columns, and `y` followed by
+    /// a character for four or more.
+    pub fn from_apgcode(code: &str) -> Result<Self, &'static str> {
+        let code = code.trim();
+        let cells = match code.split_once('_') {
+            Some((prefix, cells)) if prefix.starts_with('x') => cells,
+            Some(_) => return Err("apgcode doesn't describe a pattern"),
+            None => code,
+        };
+
+        let mut alive = Vec::new();
+        let mut width = 0;
+        for (strip, columns) in cells.split('z').enumerate() {
+            let mut x = 0;
+            let mut chars = columns.chars();
+            while let Some(c) = chars.next() {
+                match c {
+                    'w' => x += 2,
+                    'x' => x += 3,
+                    'y' => {
+                        let count = chars
+                            .next()
+                            .and_then(|c| c.to_digit(36))
+                            .ok_or("apgcode has an invalid run of empty columns")?;
+                        x += 4 + count as usize;
+                    }
+                    c => {
+                        let bits = c.to_digit(32).ok_or("unexpected character in apgcode")?;
+                        for row in (0..5).filter(|row| bits & (1 << row) != 0) {
+                            alive.push((x, strip * 5 + row));
+                        }
+                        x += 1;
+                    }
+                }
+            }
+            width = width.max(x);
+        }
+
+        let height = alive.iter().map(|&(_, y)| y + 1).max().unwrap_or(0);
+        let mut pattern = Pattern::new((width, height));
+        for cell in alive {
+            pattern.set(cell, true);
+        }
+
+        pattern
+            .trimmed()
+            .map(|(_, pattern)| pattern)
+            .ok_or("apgcode has no live cells")
+    }
+
     pub fn place(
         &self,
         world: &mut BitSlice<Lsb0, usize>,
@@ -81,8 +429,31 @@ impl Pattern {
     }
 }
 
+/// The smallest rectangle containing every live cell of a world, as the
+/// positions of its upper left and lower right corners.
+pub(crate) fn bounding_box(
+    world: &BitSlice<Lsb0, usize>,
+    world_size: (usize, usize),
+) -> Option<((usize, usize), (usize, usize))> {
+    let mut ones = world.iter_ones();
+    let first = ones.next()?;
+    let mut min = (first % world_size.0, first / world_size.0);
+    let mut max = min;
+
+    for index in ones {
+        let (x, y) = (index % world_size.0, index / world_size.0);
+        min.0 = min.0.min(x);
+        max.0 = max.0.max(x);
+        max.1 = y;
+    }
+
+    Some((min, max))
+}
+
 #[cfg(test)]
 mod test {
+    use proptest::prelude::*;
+
     use super::*;
 
     #[test]
@@ -97,4 +468,146 @@ mod test {
         assert_eq!(pattern.size, (3, 3));
         assert_eq!(pattern.store, bits![0, 1, 0, 0, 0, 1, 1, 1, 1]);
     }
+
+    #[test]
+    fn rle_pattern() {
+        let rle = "#N Glider\n\
+                   x = 3, y = 3, rule = B3/S23\n\
+                   bob$2bo$3o!";
+        let pattern = Pattern::from_rle(rle).unwrap();
+        let glider = Pattern::from_plaintext(".O.\n..O\nOOO".lines()).unwrap();
+
+        assert_eq!(pattern, glider);
+        assert_eq!(
+            glider.to_rle(Some(&LifeLike::new("B3/S23").unwrap())),
+            "x = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n"
+        );
+
+        assert!(Pattern::from_rle("bob$2bo$3o!").is_err());
+        assert!(Pattern::from_rle("x = 2, y = 2\n3o!").is_err());
+
+        // sizes that overflow, or would take too much memory, are refused
+        assert!(Pattern::from_rle("x = 5000000000, y = 5000000000\no!").is_err());
+        assert!(Pattern::from_rle("x = 200000, y = 200000\no!").is_err());
+        assert!(Pattern::from_rle("x = 2, y = 2\n18446744073709551615b2o!").is_err());
+    }
+
+    #[test]
+    fn rle_skips_empty_rows() {
+        let mut pattern = Pattern::new((4, 5));
+        pattern.set((3, 1), true);
+        pattern.set((0, 3), true);
+
+        let rle = pattern.to_rle(None);
+        assert_eq!(rle, "x = 4, y = 5\n$3bo2$o!\n");
+        assert_eq!(Pattern::from_rle(&rle).unwrap(), pattern);
+    }
+
+    #[test]
+    fn file_formats() {
+        let glider = Pattern::from_plaintext(".O.\n..O\nOOO".lines()).unwrap();
+        let rle = "#C a glider\nx = 3, y = 3\nbo$2bo$3o!";
+        assert_eq!(Pattern::from_file("glider.rle", rle).unwrap(), glider);
+        assert_eq!(Pattern::from_file("clipboard", rle).unwrap(), glider);
+        assert_eq!(
+            Pattern::from_file("glider.cells", "!Name: Glider\n.O.\n..O\nOOO").unwrap(),
+            glider
+        );
+        assert!(Pattern::from_file("glider.cells", rle).is_err());
+    }
+
+    #[test]
+    fn apgcode_pattern() {
+        let block = Pattern::from_plaintext("OO\nOO".lines()).unwrap();
+        assert_eq!(Pattern::from_apgcode("xs4_33").unwrap(), block);
+
+        let glider = Pattern::from_plaintext("OOO\n..O\n.O.".lines()).unwrap();
+        assert_eq!(Pattern::from_apgcode("xq4_153").unwrap(), glider);
+
+        // empty columns, and a second strip of rows
+        let spaced = Pattern::from_apgcode("xp2_7y071").unwrap();
+        assert_eq!(spaced.size(), (7, 3));
+        assert_eq!(spaced.population(), 7);
+        let stacked = Pattern::from_apgcode("3z3").unwrap();
+        assert_eq!(stacked.size(), (1, 7));
+        assert!(stacked.get((0, 5)) && !stacked.get((0, 4)));
+
+        assert!(Pattern::from_apgcode("yl144_1_16_afb5f3db909e60548f086e22ee3353ac").is_err());
+        assert!(Pattern::from_apgcode("xs0_0").is_err());
+    }
+
+    #[test]
+    fn region_round_trip() {
+        let glider = Pattern::from_plaintext(".O.\n..O\nOOO".lines()).unwrap();
+        let mut world: BitVec<Lsb0, usize> = BitVec::repeat(false, 8 * 6);
+        glider.place(&mut world, (8, 6), (4, 2)).unwrap();
+
+        assert_eq!(
+            Pattern::from_region(&world, (8, 6), (4, 2), (3, 3)).unwrap(),
+            glider
+        );
+        assert!(Pattern::from_region(&world, (8, 6), (6, 2), (3, 3)).is_err());
+    }
+
+    #[test]
+    fn trim_empty_edges() {
+        let padded = Pattern::from_plaintext("....\n..O.\n.OO.\n....".lines()).unwrap();
+        let (position, trimmed) = padded.trimmed().unwrap();
+
+        assert_eq!(position, (1, 1));
+        assert_eq!(trimmed, Pattern::from_plaintext(".O\nOO".lines()).unwrap());
+        assert_eq!(Pattern::new((3, 3)).trimmed(), None);
+    }
+
+    #[test]
+    fn blinker_evolves() {
+        let life = LifeLike::new("B3/S23").unwrap();
+        let blinker = Pattern::from_plaintext("OOO".lines()).unwrap();
+        let next = blinker.evolve(&life);
+
+        assert_eq!(next.size(), (5, 3));
+        assert_eq!(
+            next.trimmed(),
+            Some(((2, 0), Pattern::from_plaintext("O\nO\nO".lines()).unwrap()))
+        );
+    }
+
+    #[test]
+    fn symmetries_of_glider() {
+        let glider = Pattern::from_plaintext(".O.\n..O\nOOO".lines()).unwrap();
+        let rotated = Pattern::from_plaintext("O..\nO.O\nOO.".lines()).unwrap();
+
+        assert_eq!(glider.rotate_clockwise(), rotated);
+        assert_eq!(
+            glider
+                .rotate_clockwise()
+                .rotate_clockwise()
+                .rotate_clockwise()
+                .rotate_clockwise(),
+            glider
+        );
+        assert_eq!(glider.flip_horizontal().flip_horizontal(), glider);
+
+        let mut images = glider.symmetries().to_vec();
+        images.sort();
+        images.dedup();
+        assert_eq!(images.len(), 8);
+    }
+
+    proptest! {
+        #[test]
+        fn pt_rle_round_trips(
+            (width, cells) in (1usize..90, 1usize..12)
+                .prop_flat_map(|(w, h)| (Just(w), proptest::collection::vec(any::<bool>(), w * h)))
+        ) {
+            let mut pattern = Pattern::new((width, cells.len() / width));
+            for (index, &alive) in cells.iter().enumerate() {
+                pattern.set((index % width, index / width), alive);
+            }
+
+            let rle = pattern.to_rle(None);
+            prop_assert!(rle.lines().all(|line| line.len() <= 70));
+            prop_assert_eq!(Pattern::from_rle(&rle).unwrap(), pattern);
+        }
+    }
 }
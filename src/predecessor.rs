@@ -0,0 +1,235 @@
+//! Searches for patterns that evolve into a given pattern in one generation.
+//!
+//! The search is a backtracking solver over the cells of a bounded box around
+//! the target. Cells are assigned in row-major order, and after every
+//! assignment each affected cell of the next generation is checked to see
+//! whether some choice of the still unassigned cells could give it the state
+//! the target asks for. If the whole box is searched without success, no
+//! predecessor fits inside that box.
+
+use crate::{pattern::Pattern, LifeLike};
+
+/// A cell of the next generation, together with the cells of the box that
+/// decide its state.
+struct Output {
+    center: Option<usize>,
+    neighbors: Vec<usize>,
+    alive: bool,
+}
+
+struct Search<'a> {
+    machine: &'a LifeLike,
+    cells: Vec<Option<bool>>,
+    outputs: Vec<Output>,
+    /// For each cell of the box, the outputs whose neighborhood includes it.
+    watchers: Vec<Vec<usize>>,
+}
+
+impl<'a> Search<'a> {
+    fn new(target: &Pattern, machine: &'a LifeLike, margin: usize) -> Self {
+        let (width, height) = target.size();
+        let size = (width + 2 * margin, height + 2 * margin);
+        let index = |x: isize, y: isize| {
+            if (0..size.0 as isize).contains(&x) && (0..size.1 as isize).contains(&y) {
+                Some(x as usize + (y as usize * size.0))
+            } else {
+                None
+            }
+        };
+
+        let mut outputs = Vec::new();
+        let mut watchers = vec![Vec::new(); size.0 * size.1];
+
+        for y in -1..=(size.1 as isize) {
+            for x in -1..=(size.0 as isize) {
+                let (tx, ty) = (x - margin as isize, y - margin as isize);
+                let alive = (0..width as isize).contains(&tx)
+                    && (0..height as isize).contains(&ty)
+                    && target.get((tx as usize, ty as usize));
+
+                let neighbors: Vec<usize> = [
+                    (-1, -1),
+                    (0, -1),
+                    (1, -1),
+                    (1, 0),
+                    (1, 1),
+                    (0, 1),
+                    (-1, 1),
+                    (-1, 0),
+                ]
+                .iter()
+                .filter_map(|(dx, dy)| index(x + dx, y + dy))
+                .collect();
+                let center = index(x, y);
+
+                for &cell in neighbors.iter().chain(center.iter()) {
+                    watchers[cell].push(outputs.len());
+                }
+                outputs.push(Output {
+                    center,
+                    neighbors,
+                    alive,
+                });
+            }
+        }
+
+        Search {
+            machine,
+            cells: vec![None; size.0 * size.1],
+            outputs,
+            watchers,
+        }
+    }
+
+    /// Whether some assignment of the undecided cells gives `output` the right
+    /// state.
+    fn feasible(&self, output: &Output) -> bool {
+        let alive = output
+            .neighbors
+            .iter()
+            .filter(|&&cell| self.cells[cell] == Some(true))
+            .count();
+        let unknown = output
+            .neighbors
+            .iter()
+            .filter(|&&cell| self.cells[cell].is_none())
+            .count();
+
+        let statuses: &[bool] = match output.center.map(|cell| self.cells[cell]) {
+            None | Some(Some(false)) => &[false],
+            Some(Some(true)) => &[true],
+            Some(None) => &[false, true],
+        };
+
+        statuses.iter().any(|&status| {
+            (alive..=alive + unknown)
+                .any(|neighbors| self.machine.simulate(status, neighbors) == output.alive)
+        })
+    }
+
+    fn run(&mut self) -> bool {
+        let total = self.cells.len();
+        // the next value to try for every cell: 0 is dead, 1 is alive, 2 is exhausted
+        let mut next = vec![0u8; total];
+        let mut current = 0;
+
+        loop {
+            if current == total {
+                return true;
+            }
+
+            if next[current] == 2 {
+                next[current] = 0;
+                self.cells[current] = None;
+                if current == 0 {
+                    return false;
+                }
+                current -= 1;
+                continue;
+            }
+
+            self.cells[current] = Some(next[current] == 1);
+            next[current] += 1;
+
+            if self.watchers[current]
+                .iter()
+                .all(|&output| self.feasible(&self.outputs[output]))
+            {
+                current += 1;
+            }
+        }
+    }
+}
+
+/// Looks for a pattern that becomes `target` after one generation of
+/// `machine`, using only cells within `margin` cells of the target.
+///
+/// The returned pattern is the whole search box, so the target lines up with
+/// it at offset `(margin, margin)`. `Ok(None)` means that no predecessor
+/// exists inside the box, which makes the target a Garden of Eden if the box
+/// is large enough to rule out every predecessor.
+pub fn find_predecessor(
+    target: &Pattern,
+    machine: &LifeLike,
+    margin: usize,
+) -> Result<Option<Pattern>, &'static str> {
+    if machine.simulate(false, 0) {
+        return Err("predecessor search does not support rules with B0");
+    }
+
+    let mut search = Search::new(target, machine, margin);
+    if !search.run() {
+        return Ok(None);
+    }
+
+    let (width, height) = target.size();
+    let size = (width + 2 * margin, height + 2 * margin);
+    let mut predecessor = Pattern::new(size);
+    for (index, cell) in search.cells.iter().enumerate() {
+        predecessor.set((index % size.0, index / size.0), *cell == Some(true));
+    }
+
+    Ok(Some(predecessor))
+}
+
+/// Whether `target` has no predecessor within `margin` cells of itself.
+pub fn is_garden_of_eden(
+    target: &Pattern,
+    machine: &LifeLike,
+    margin: usize,
+) -> Result<bool, &'static str> {
+    Ok(find_predecessor(target, machine, margin)?.is_none())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::grid::Grid;
+
+    fn evolves_into(predecessor: &Pattern, target: &Pattern, machine: &LifeLike) -> bool {
+        let mut grid = Grid::from_pattern(predecessor, 2);
+        grid.step(machine);
+        let trimmed = Grid::from_pattern(target, 0).to_pattern().map(|(_, p)| p);
+        grid.to_pattern().map(|(_, p)| p) == trimmed
+    }
+
+    #[test]
+    fn single_cell_needs_room() {
+        let life = LifeLike::new("B3/S23").unwrap();
+        let cell = Pattern::from_plaintext("O".lines()).unwrap();
+
+        assert_eq!(find_predecessor(&cell, &life, 0), Ok(None));
+
+        let predecessor = find_predecessor(&cell, &life, 1).unwrap().unwrap();
+        assert_eq!(predecessor.size(), (3, 3));
+        assert!(evolves_into(&predecessor, &cell, &life));
+    }
+
+    #[test]
+    fn glider_has_predecessor() {
+        let life = LifeLike::new("B3/S23").unwrap();
+        let glider = Pattern::from_plaintext(".O.\n..O\nOOO".lines()).unwrap();
+
+        let predecessor = find_predecessor(&glider, &life, 1).unwrap().unwrap();
+        assert!(evolves_into(&predecessor, &glider, &life));
+        assert!(!is_garden_of_eden(&glider, &life, 1).unwrap());
+    }
+
+    #[test]
+    fn orphan_in_seeds() {
+        // under B2/S every cell of the square has to be born, so all of them
+        // start out dead and the middle cell has no live neighbors to be born from
+        let seeds = LifeLike::new("B2/S").unwrap();
+        let full = Pattern::from_plaintext("OOO\nOOO\nOOO".lines()).unwrap();
+
+        assert!(is_garden_of_eden(&full, &seeds, 1).unwrap());
+    }
+
+    #[test]
+    fn rejects_b0() {
+        let rule = LifeLike::new("B0/S8").unwrap();
+        let cell = Pattern::from_plaintext("O".lines()).unwrap();
+
+        assert!(find_predecessor(&cell, &rule, 1).is_err());
+    }
+}
@@ -0,0 +1,139 @@
+//! Finds the range of Life-like rules in which a pattern behaves the same.
+//!
+//! Running a pattern only ever exercises some of the transitions in the rule
+//! table. The others can be switched on or off without changing anything, so
+//! the pattern works identically in every rule between the minimal rule (all
+//! unexercised transitions off) and the maximal rule (all of them on).
+
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::{pattern::Pattern, LifeLike};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RuleRange {
+    /// The rule with every unexercised transition switched off.
+    pub min: LifeLike,
+    /// The rule with every unexercised transition switched on.
+    pub max: LifeLike,
+    /// How many generations were examined.
+    pub generations: usize,
+    /// Whether the pattern was seen to repeat itself, possibly displaced, in
+    /// which case the range holds for every later generation as well.
+    pub complete: bool,
+}
+
+impl RuleRange {
+    /// Whether the pattern behaves the same under `rule` as under the rule the
+    /// range was computed with.
+    pub fn contains(&self, rule: &LifeLike) -> bool {
+        (0..=8).all(|neighbors| {
+            [false, true].iter().all(|&status| {
+                let min = self.min.simulate(status, neighbors);
+                let max = self.max.simulate(status, neighbors);
+                min != max || rule.simulate(status, neighbors) == min
+            })
+        })
+    }
+}
+
+impl fmt::Display for RuleRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} - {}", self.min, self.max)
+    }
+}
+
+/// Runs `pattern` under `machine` for up to `max_generations`, or until it
+/// repeats, and reports which rules it behaves identically in.
+pub fn rule_range(
+    pattern: &Pattern,
+    machine: &LifeLike,
+    max_generations: usize,
+) -> Result<RuleRange, &'static str> {
+    if machine.simulate(false, 0) {
+        return Err("rule ranges are not supported for rules with B0");
+    }
+
+    // dead cells far from the pattern always use the B0 transition
+    let mut exercised = [false; 18];
+    exercised[LifeLike::encode_index(false, 0)] = true;
+
+    let mut seen = HashSet::new();
+    let mut current = match pattern.trimmed() {
+        Some((_, trimmed)) => trimmed,
+        None => Pattern::new((0, 0)),
+    };
+    let mut generations = 0;
+    let mut complete = false;
+
+    while generations < max_generations {
+        if !seen.insert(current.clone()) {
+            complete = true;
+            break;
+        }
+
+        for (_, status, neighbors) in current.neighborhoods() {
+            exercised[LifeLike::encode_index(status, neighbors)] = true;
+        }
+
+        current = match current.evolve(machine).trimmed() {
+            Some((_, next)) => next,
+            None => Pattern::new((0, 0)),
+        };
+        generations += 1;
+    }
+
+    let mut min = [false; 18];
+    let mut max = [false; 18];
+    for index in 0..18 {
+        let (status, neighbors) = LifeLike::decode_index(index);
+        let rule = machine.simulate(status, neighbors);
+        min[index] = rule && exercised[index];
+        max[index] = rule || !exercised[index];
+    }
+
+    Ok(RuleRange {
+        min: LifeLike::from_rules(min),
+        max: LifeLike::from_rules(max),
+        generations,
+        complete,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn block_range() {
+        let life = LifeLike::new("B3/S23").unwrap();
+        let block = Pattern::from_plaintext("OO\nOO".lines()).unwrap();
+        let range = rule_range(&block, &life, 100).unwrap();
+
+        assert_eq!(range.to_string(), "B/S3 - B345678/S012345678");
+        assert_eq!(range.generations, 1);
+        assert!(range.complete);
+    }
+
+    #[test]
+    fn glider_range() {
+        let life = LifeLike::new("B3/S23").unwrap();
+        let glider = Pattern::from_plaintext(".O.\n..O\nOOO".lines()).unwrap();
+        let range = rule_range(&glider, &life, 100).unwrap();
+
+        assert!(range.complete);
+        assert!(range.contains(&life));
+        assert!(range.contains(&LifeLike::new("B36/S23").unwrap()));
+        assert!(!range.contains(&LifeLike::new("B2/S23").unwrap()));
+    }
+
+    #[test]
+    fn incomplete_when_limited() {
+        let life = LifeLike::new("B3/S23").unwrap();
+        let r = Pattern::from_plaintext(".OO\nOO.\n.O.".lines()).unwrap();
+        let range = rule_range(&r, &life, 10).unwrap();
+
+        assert_eq!(range.generations, 10);
+        assert!(!range.complete);
+    }
+}
@@ -0,0 +1,149 @@
+//! A file format for the complete state of a simulation, so that it can be
+//! saved and picked up again later by the viewer or by other tools.
+//!
+//! Files are plain text. The first line names the format and its version,
+//! followed by `key = value` lines, followed by the whole world as RLE:
+//!
+//! ```text
+//! life-snapshot 1
+//! rule = B3/S23
+//! generation = 42
+//! speed = 0
+//! x = 8, y = 6, rule = B3/S23
+//! 2$3bo$4bo$2b3o!
+//! ```
+//!
+//! The size of the world is the size of the RLE pattern. Readers ignore keys
+//! they don't know, so later versions can add keys without breaking them.
+
+use crate::{grid::Grid, pattern::Pattern, LifeLike};
+
+/// The version written to new files. Files with a newer version are refused.
+pub const VERSION: u32 = 1;
+const MAGIC: &str = "life-snapshot";
+
+#[derive(Clone, Debug)]
+pub struct Snapshot {
+    pub rule: LifeLike,
+    /// The world, with its size and generation.
+    pub grid: Grid,
+    /// The viewer's speed setting, as the power of two it scales the base
+    /// rate by. Tools without a notion of speed can leave it at zero.
+    pub speed: i32,
+}
+
+impl Snapshot {
+    /// Writes the snapshot in the current version of the format.
+    pub fn write(&self) -> String {
+        let world = Pattern::from_region(
+            self.grid.cells(),
+            self.grid.size(),
+            (0, 0),
+            self.grid.size(),
+        )
+        .expect("the world lies inside itself");
+
+        format!(
+            "{} {}\nrule = {}\ngeneration = {}\nspeed = {}\n{}",
+            MAGIC,
+            VERSION,
+            self.rule,
+            self.grid.generation(),
+            self.speed,
+            world.to_rle(Some(&self.rule))
+        )
+    }
+
+    /// Reads a snapshot written by this or an earlier version of the format.
+    pub fn read(text: &str) -> Result<Self, &'static str> {
+        let mut lines = text.lines();
+
+        let version = lines
+            .next()
+            .and_then(|line| line.trim().strip_prefix(MAGIC))
+            .and_then(|version| version.trim().parse::<u32>().ok())
+            .ok_or("not a snapshot file")?;
+        if version > VERSION {
+            return Err("snapshot was written by a newer version");
+        }
+
+        let (mut rule, mut generation, mut speed) = (None, None, 0);
+        let mut rle = String::new();
+        for line in lines.by_ref() {
+            let line = line.trim();
+            let pair = line.split_once('=').map(|(k, v)| (k.trim(), v.trim()));
+            // the RLE header starts with the width, as `x = 8`
+            if matches!(pair, Some(("x", _))) {
+                rle.push_str(line);
+                rle.push('\n');
+                break;
+            }
+
+            match pair {
+                Some(("rule", value)) => rule = Some(LifeLike::new(value)?),
+                Some(("generation", value)) => {
+                    generation = Some(value.parse().map_err(|_| "invalid generation")?)
+                }
+                Some(("speed", value)) => speed = value.parse().map_err(|_| "invalid speed")?,
+                _ => {}
+            }
+        }
+
+        for line in lines {
+            rle.push_str(line);
+            rle.push('\n');
+        }
+
+        let world = Pattern::from_rle(&rle)?;
+        let mut grid = Grid::new(world.size());
+        world.place(grid.cells_mut(), world.size(), (0, 0))?;
+        grid.set_generation(generation.ok_or("snapshot is missing the generation")?);
+
+        Ok(Snapshot {
+            rule: rule.ok_or("snapshot is missing the rule")?,
+            grid,
+            speed,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let glider = Pattern::from_plaintext(".O.\n..O\nOOO".lines()).unwrap();
+        let mut grid = Grid::from_pattern(&glider, 3);
+        let highlife = LifeLike::new("B36/S23").unwrap();
+        grid.step(&highlife);
+
+        let snapshot = Snapshot {
+            rule: highlife.clone(),
+            grid,
+            speed: -2,
+        };
+        let text = snapshot.write();
+        assert!(text.starts_with("life-snapshot 1\n"));
+
+        let read = Snapshot::read(&text).unwrap();
+        assert_eq!(read.rule, highlife);
+        assert_eq!(read.speed, -2);
+        assert_eq!(read.grid.size(), (9, 9));
+        assert_eq!(read.grid.generation(), 1);
+        assert_eq!(read.grid.cells(), snapshot.grid.cells());
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert!(Snapshot::read("x = 3, y = 3\nbo$2bo$3o!").is_err());
+        assert!(Snapshot::read("life-snapshot 2\nrule = B3/S23").is_err());
+        assert!(Snapshot::read("life-snapshot 1\ngeneration = 0\nx = 1, y = 1\no!").is_err());
+
+        // unknown keys are skipped
+        let text = "life-snapshot 1\nrule = B3/S23\ngeneration = 5\ncolor = red\nx = 2, y = 1\no!";
+        assert_eq!(Snapshot::read(text).unwrap().grid.population(), 1);
+        let text = "life-snapshot 1\nrule = B3/S23\ngeneration = 5\nxoffset = 3\nx = 2, y = 1\no!";
+        assert_eq!(Snapshot::read(text).unwrap().grid.size(), (2, 1));
+    }
+}
@@ -0,0 +1,113 @@
+//! Running the simulation at a set number of generations per second, whatever
+//! the frame rate of the front end drawing it.
+
+use std::fmt;
+
+/// Generations per second at an exponent of zero.
+pub const BASE_RATE: f64 = 60.;
+pub const MIN_EXPONENT: i32 = -6;
+pub const MAX_EXPONENT: i32 = 12;
+/// Longer frames (say, after the window was hidden or the process was
+/// suspended) don't make the simulation try to catch up.
+const MAX_FRAME_TIME: f64 = 0.1;
+
+/// Runs the simulation at a target number of generations per second, no
+/// matter the frame rate. Like Golly, the rate doubles with each step of the
+/// exponent: zero is 60 generations per second, 1 is 120, -1 is 30.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Speed {
+    exponent: i32,
+    /// Generations that are due but haven't been run yet.
+    owed: f64,
+}
+
+impl Speed {
+    pub fn new(exponent: i32) -> Self {
+        Speed {
+            exponent: exponent.clamp(MIN_EXPONENT, MAX_EXPONENT),
+            owed: 0.,
+        }
+    }
+
+    pub fn exponent(&self) -> i32 {
+        self.exponent
+    }
+
+    pub fn set_exponent(&mut self, exponent: i32) {
+        *self = Speed::new(exponent);
+    }
+
+    pub fn faster(&mut self) {
+        self.set_exponent(self.exponent + 1);
+    }
+
+    pub fn slower(&mut self) {
+        self.set_exponent(self.exponent - 1);
+    }
+
+    /// The target number of generations per second.
+    pub fn rate(&self) -> f64 {
+        BASE_RATE * 2f64.powi(self.exponent)
+    }
+
+    /// Calls `step` once for every generation that came due during the last
+    /// frame, which took `frame_time` seconds, stopping early once
+    /// `out_of_time` says the frame's budget has run out, so that drawing and
+    /// input stay responsive. Generations that didn't fit are dropped rather
+    /// than piling up.
+    pub fn run(&mut self, frame_time: f64, out_of_time: impl Fn() -> bool, mut step: impl FnMut()) {
+        self.owed += self.rate() * frame_time.min(MAX_FRAME_TIME);
+
+        while self.owed >= 1. {
+            step();
+            self.owed -= 1.;
+
+            if out_of_time() {
+                self.owed = self.owed.min(1.);
+                break;
+            }
+        }
+    }
+}
+
+impl Default for Speed {
+    fn default() -> Self {
+        Speed::new(0)
+    }
+}
+
+impl fmt::Display for Speed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rate = self.rate();
+        if rate >= 1. {
+            write!(f, "{} gen/s (2^{})", rate, self.exponent)
+        } else {
+            write!(f, "{:.2} gen/s (2^{})", rate, self.exponent)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn runs_generations_as_they_come_due() {
+        let mut speed = Speed::new(1);
+        assert_eq!(speed.rate(), 120.);
+
+        let mut steps = 0;
+        speed.run(0.05, || false, || steps += 1);
+        assert_eq!(steps, 6);
+        // long frames are cut short, and so is running out of time
+        speed.run(10., || false, || steps += 1);
+        assert_eq!(steps, 18);
+        speed.run(0.05, || true, || steps += 1);
+        assert_eq!(steps, 19);
+
+        let mut slow = Speed::new(-6);
+        slow.run(0.01, || false, || steps += 1);
+        assert_eq!(steps, 19);
+        assert_eq!(Speed::new(100).exponent(), MAX_EXPONENT);
+    }
+}
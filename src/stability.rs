@@ -0,0 +1,435 @@
+//! Detects when a pattern stops changing apart from periodic oscillation and
+//! spaceships flying away, as used to measure the lifespan of soups and
+//! methuselahs.
+//!
+//! Patterns are run in a [`Grid`], which wraps around at the edges. To keep the
+//! wrap from feeding debris back into the pattern, escaping spaceships are
+//! deleted once they are clear of the remaining ash, and the world is
+//! re-centered (and grown when needed) whenever live cells approach an edge.
+//!
+//! Only the glider and the lightweight, middleweight and heavyweight
+//! spaceships are recognized. Patterns that send out any other spaceship
+//! keep growing until they hit [`Limits::max_size`].
+
+use std::collections::{hash_map::DefaultHasher, HashMap};
+use std::hash::{Hash, Hasher};
+
+use crate::{grid::Grid, pattern::Pattern, Automata};
+
+/// Empty cells kept between the pattern and the edges of the world.
+const MARGIN: usize = 16;
+
+/// How far a spaceship has to be past the ash before it counts as escaped.
+const ESCAPE_GAP: usize = 8;
+
+/// The spaceships that are deleted once they escape, glider first. Each one
+/// moves in four generations in Life.
+const SPACESHIPS: [&str; 4] = [
+    ".O.\n..O\nOOO",
+    ".O..O\nO....\nO...O\nOOOO.",
+    "...O..\n.O...O\nO.....\nO....O\nOOOOO.",
+    "...OO..\n.O....O\nO......\nO.....O\nOOOOOO.",
+];
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Limits {
+    /// Give up if the pattern is still active after this many generations.
+    pub max_generations: usize,
+    /// The longest oscillator period that is recognized.
+    pub max_period: usize,
+    /// The largest world the pattern may grow into before giving up.
+    pub max_size: (usize, usize),
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_generations: 100_000,
+            max_period: 60,
+            max_size: (4096, 4096),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Stabilization {
+    /// The first generation from which the pattern (minus escaped spaceships)
+    /// only repeats itself.
+    pub generation: usize,
+    /// The period of the final ash, where still lifes have period 1.
+    pub period: usize,
+    /// The number of live cells left once the pattern stabilized, not counting
+    /// escaped spaceships.
+    pub population: usize,
+    /// How many gliders escaped from the pattern.
+    pub gliders: usize,
+    /// How many lightweight, middleweight and heavyweight spaceships escaped
+    /// from the pattern.
+    pub spaceships: usize,
+}
+
+/// Runs `pattern` under `machine` until it stabilizes.
+pub fn stabilize<A: Automata>(
+    pattern: &Pattern,
+    machine: &A,
+    limits: &Limits,
+) -> Result<Stabilization, &'static str> {
+    let ships = spaceship_phases(machine);
+    let mut grid = Grid::from_pattern(pattern, MARGIN);
+
+    // cells keep fixed coordinates relative to `origin` across re-centering
+    let mut origin = (0isize, 0isize);
+    let mut fingerprints: Vec<u64> = Vec::new();
+    let (mut gliders, mut spaceships) = (0, 0);
+
+    loop {
+        for ship in remove_escaped_spaceships(&mut grid, &ships) {
+            if ship.glider {
+                gliders += 1;
+            } else {
+                spaceships += 1;
+            }
+        }
+
+        if let Some(size) = needs_room(&grid, limits)? {
+            let before = grid.bounding_box().map(|(min, _)| min);
+            grid.recenter(size)?;
+            if let (Some(before), Some((after, _))) = (before, grid.bounding_box()) {
+                origin.0 += after.0 as isize - before.0 as isize;
+                origin.1 += after.1 as isize - before.1 as isize;
+            }
+        }
+
+        fingerprints.push(fingerprint(&grid, origin));
+
+        let now = fingerprints.len() - 1;
+        for period in 1..=limits.max_period.min(now) {
+            if fingerprints[now] == fingerprints[now - period] {
+                let mut start = now - period;
+                while start > 0 && fingerprints[start - 1] == fingerprints[start - 1 + period] {
+                    start -= 1;
+                }
+
+                return Ok(Stabilization {
+                    generation: start,
+                    period,
+                    population: grid.population(),
+                    gliders,
+                    spaceships,
+                });
+            }
+        }
+
+        if grid.generation() >= limits.max_generations {
+            return Err("pattern did not stabilize within the generation limit");
+        }
+
+        grid.step(machine);
+    }
+}
+
+/// Decides whether live cells have come too close to the edge of the world,
+/// and if so, what size the re-centered world should have.
+fn needs_room(grid: &Grid, limits: &Limits) -> Result<Option<(usize, usize)>, &'static str> {
+    let size = grid.size();
+    let (min, max) = match grid.bounding_box() {
+        Some(bounds) => bounds,
+        None => return Ok(None),
+    };
+
+    let edge = MARGIN / 2;
+    if min.0 >= edge && min.1 >= edge && max.0 + edge < size.0 && max.1 + edge < size.1 {
+        return Ok(None);
+    }
+
+    let grow = |current: usize, wanted: usize| {
+        if wanted <= current {
+            current
+        } else {
+            (current * 2).max(wanted)
+        }
+    };
+    let new_size = (
+        grow(size.0, max.0 - min.0 + 1 + 2 * MARGIN),
+        grow(size.1, max.1 - min.1 + 1 + 2 * MARGIN),
+    );
+
+    if new_size.0 > limits.max_size.0 || new_size.1 > limits.max_size.1 {
+        return Err("pattern outgrew the maximum world size");
+    }
+
+    Ok(Some(new_size))
+}
+
+/// Hashes the live cells by their position relative to `origin`, so that
+/// re-centering the world does not change the result.
+fn fingerprint(grid: &Grid, origin: (isize, isize)) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    if let Some((min, pattern)) = grid.to_pattern() {
+        (min.0 as isize - origin.0).hash(&mut hasher);
+        (min.1 as isize - origin.1).hash(&mut hasher);
+        pattern.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// A phase of one of the [`SPACESHIPS`].
+#[derive(Copy, Clone, Debug)]
+struct Ship {
+    /// The direction it travels in.
+    direction: (isize, isize),
+    glider: bool,
+}
+
+/// Finds every phase and orientation of the [`SPACESHIPS`] that actually fly
+/// under `machine`.
+fn spaceship_phases<A: Automata>(machine: &A) -> HashMap<Pattern, Ship> {
+    let mut phases = HashMap::new();
+
+    for (index, ship) in SPACESHIPS.iter().enumerate() {
+        let ship = Pattern::from_plaintext(ship.lines()).unwrap();
+        for shape in ship.symmetries().iter() {
+            let mut grid = Grid::from_pattern(shape, 4);
+            let start = grid.bounding_box().unwrap().0;
+
+            let mut seen = Vec::with_capacity(4);
+            for _ in 0..4 {
+                match grid.to_pattern() {
+                    Some((_, phase)) => seen.push(phase),
+                    None => break,
+                }
+                grid.step(machine);
+            }
+
+            if let Some((end, pattern)) = grid.to_pattern() {
+                let direction = (
+                    end.0 as isize - start.0 as isize,
+                    end.1 as isize - start.1 as isize,
+                );
+                if &pattern == shape && direction != (0, 0) {
+                    for phase in seen {
+                        let glider = index == 0;
+                        phases.insert(phase, Ship { direction, glider });
+                    }
+                }
+            }
+        }
+    }
+
+    phases
+}
+
+/// Deletes spaceships that are isolated and moving away from everything else,
+/// returning the ones that were removed.
+fn remove_escaped_spaceships(grid: &mut Grid, ships: &HashMap<Pattern, Ship>) -> Vec<Ship> {
+    let largest = match ships.keys().map(Pattern::population).max() {
+        Some(largest) => largest,
+        None => return Vec::new(),
+    };
+
+    let size = grid.size();
+    let mut found = Vec::new();
+    let mut ash: Option<((usize, usize), (usize, usize))> = None;
+
+    for component in components(grid) {
+        let (min, max) = bounds(&component, size);
+        let ship = if component.len() <= largest {
+            Pattern::from_region(
+                grid.cells(),
+                size,
+                min,
+                (max.0 - min.0 + 1, max.1 - min.1 + 1),
+            )
+            .ok()
+            .and_then(|pattern| ships.get(&pattern).copied())
+        } else {
+            None
+        };
+
+        match ship {
+            Some(ship) => found.push((component, min, max, ship)),
+            None => {
+                ash = Some(match ash {
+                    Some((a, b)) => (
+                        (a.0.min(min.0), a.1.min(min.1)),
+                        (b.0.max(max.0), b.1.max(max.1)),
+                    ),
+                    None => (min, max),
+                })
+            }
+        }
+    }
+
+    let mut removed = Vec::new();
+    for (component, min, max, ship) in found {
+        if !isolated(grid, &component, min, max) {
+            continue;
+        }
+
+        let direction = ship.direction;
+        let escaping = match ash {
+            None => true,
+            Some((ash_min, ash_max)) => {
+                (direction.0 > 0 && min.0 > ash_max.0 + ESCAPE_GAP)
+                    || (direction.0 < 0 && max.0 + ESCAPE_GAP < ash_min.0)
+                    || (direction.1 > 0 && min.1 > ash_max.1 + ESCAPE_GAP)
+                    || (direction.1 < 0 && max.1 + ESCAPE_GAP < ash_min.1)
+            }
+        };
+
+        if escaping {
+            for index in component {
+                grid.set((index % size.0, index / size.0), false);
+            }
+            removed.push(ship);
+        }
+    }
+
+    removed
+}
+
+/// Whether the only live cells within two cells of the component are its own.
+fn isolated(grid: &Grid, component: &[usize], min: (usize, usize), max: (usize, usize)) -> bool {
+    let size = grid.size();
+    let count = (min.1.saturating_sub(2)..=(max.1 + 2).min(size.1 - 1))
+        .flat_map(|y| (min.0.saturating_sub(2)..=(max.0 + 2).min(size.0 - 1)).map(move |x| (x, y)))
+        .filter(|&cell| grid.get(cell))
+        .count();
+    count == component.len()
+}
+
+/// Splits the live cells into groups with no more than one dead cell between
+/// neighbors, since some phases of spaceships have gaps that wide.
+fn components(grid: &Grid) -> Vec<Vec<usize>> {
+    let size = grid.size();
+    let cells = grid.cells();
+    let mut visited = cells.to_bitvec();
+    visited.set_all(false);
+
+    let mut components = Vec::new();
+    for start in cells.iter_ones() {
+        if visited[start] {
+            continue;
+        }
+
+        visited.set(start, true);
+        let mut stack = vec![start];
+        let mut component = Vec::new();
+
+        while let Some(index) = stack.pop() {
+            component.push(index);
+            let (x, y) = (index % size.0, index / size.0);
+            for dy in [size.1 - 2, size.1 - 1, 0, 1, 2] {
+                for dx in [size.0 - 2, size.0 - 1, 0, 1, 2] {
+                    let neighbor = (x + dx) % size.0 + ((y + dy) % size.1) * size.0;
+                    if cells[neighbor] && !visited[neighbor] {
+                        visited.set(neighbor, true);
+                        stack.push(neighbor);
+                    }
+                }
+            }
+        }
+
+        components.push(component);
+    }
+
+    components
+}
+
+fn bounds(component: &[usize], size: (usize, usize)) -> ((usize, usize), (usize, usize)) {
+    component
+        .iter()
+        .fold(((usize::MAX, usize::MAX), (0, 0)), |(min, max), &index| {
+            let (x, y) = (index % size.0, index / size.0);
+            ((min.0.min(x), min.1.min(y)), (max.0.max(x), max.1.max(y)))
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::LifeLike;
+
+    fn run(pattern: &str) -> Stabilization {
+        let life = LifeLike::new("B3/S23").unwrap();
+        let pattern = Pattern::from_plaintext(pattern.lines()).unwrap();
+        stabilize(&pattern, &life, &Limits::default()).unwrap()
+    }
+
+    #[test]
+    fn still_life_is_stable() {
+        let block = run("OO\nOO");
+        assert_eq!(
+            block,
+            Stabilization {
+                generation: 0,
+                period: 1,
+                population: 4,
+                gliders: 0,
+                spaceships: 0
+            }
+        );
+    }
+
+    #[test]
+    fn oscillator_has_period() {
+        let blinker = run("OOO");
+        assert_eq!(blinker.generation, 0);
+        assert_eq!(blinker.period, 2);
+    }
+
+    #[test]
+    fn glider_escapes() {
+        let glider = run(".O.\n..O\nOOO");
+        assert_eq!(glider.gliders, 1);
+        assert_eq!(glider.population, 0);
+    }
+
+    #[test]
+    fn spaceships_escape() {
+        // a soup that sends out a lightweight spaceship and leaves a blinker
+        let soup = run(".OO.O\n...OO\n....O\nOO.O.\nO..OO");
+        assert_eq!(
+            soup,
+            Stabilization {
+                generation: 23,
+                period: 2,
+                population: 3,
+                gliders: 0,
+                spaceships: 1
+            }
+        );
+
+        for ship in &SPACESHIPS[2..] {
+            let ship = run(ship);
+            assert_eq!((ship.spaceships, ship.population), (1, 0));
+        }
+    }
+
+    #[test]
+    fn r_pentomino_lifespan() {
+        // the usual figure of 116 cells includes the six escaped gliders
+        let r = run(".OO\nOO.\n.O.");
+        assert_eq!(
+            r,
+            Stabilization {
+                generation: 1103,
+                period: 2,
+                population: 86,
+                gliders: 6,
+                spaceships: 0
+            }
+        );
+    }
+
+    #[test]
+    fn gives_up_at_limit() {
+        let life = LifeLike::new("B3/S23").unwrap();
+        let r = Pattern::from_plaintext(".OO\nOO.\n.O.".lines()).unwrap();
+        let limits = Limits {
+            max_generations: 100,
+            ..Limits::default()
+        };
+        assert!(stabilize(&r, &life, &limits).is_err());
+    }
+}
@@ -0,0 +1,246 @@
+//! Text drawn in cells, for labels and messages that can be left to evolve.
+//!
+//! Letters are filled straight from their outlines: a cell is alive when its
+//! center is inside a letter, by the nonzero winding rule fonts use.
+
+use ttf_parser::{Face, GlyphId, OutlineBuilder};
+
+use crate::pattern::Pattern;
+
+/// Rubik Regular, which is bundled with the repository.
+pub const RUBIK: &[u8] = include_bytes!("../assets/font/Rubik-Regular.ttf");
+
+/// The tallest lines that can be drawn, in cells.
+pub const MAX_HEIGHT: usize = 1024;
+/// The most cells text can be drawn in, counting the dead ones around the
+/// letters, so that long text can't run out of memory.
+pub const MAX_CELLS: usize = 1 << 24;
+/// Straight edges each curve of an outline is split into.
+const CURVE_STEPS: usize = 8;
+
+/// A TrueType or OpenType font to draw text with.
+pub struct Font<'a> {
+    face: Face<'a>,
+}
+
+impl<'a> Font<'a> {
+    pub fn new(bytes: &'a [u8]) -> Result<Self, &'static str> {
+        let face = Face::from_slice(bytes, 0).map_err(|_| "could not read font")?;
+        Ok(Font { face })
+    }
+
+    /// Draws `text` with each line `height` cells tall, from the top of the
+    /// font's tallest letters to the bottom of its lowest, so capitals come
+    /// out about two thirds as tall. The pattern is cut down to the live
+    /// cells, and the lines must fit in [`MAX_CELLS`] before that.
+    pub fn pattern(&self, text: &str, height: usize) -> Result<Pattern, &'static str> {
+        if height == 0 || height > MAX_HEIGHT {
+            return Err("text must be between 1 and 1024 cells tall");
+        }
+        let ascender = self.face.ascender() as i32;
+        let descender = self.face.descender() as i32;
+        if ascender <= descender {
+            return Err("font has no line height");
+        }
+        let scale = height as f32 / (ascender - descender) as f32;
+        let line = (ascender - descender + self.face.line_gap() as i32) as f32 * scale;
+        let total_height = text.lines().count() as f32 * line;
+
+        let mut outline = Outline {
+            scale,
+            ..Outline::default()
+        };
+        for (row, text) in text.lines().enumerate() {
+            outline.origin = (0., ascender as f32 * scale + row as f32 * line);
+            for character in text.chars() {
+                // characters the font lacks get its missing glyph, often a box
+                let glyph = self.face.glyph_index(character).unwrap_or(GlyphId(0));
+                self.face.outline_glyph(glyph, &mut outline);
+                outline.origin.0 += self.face.glyph_hor_advance(glyph).unwrap_or(0) as f32 * scale;
+                if outline.origin.0 * total_height > MAX_CELLS as f32 {
+                    return Err("text must fit in 2^24 cells");
+                }
+            }
+        }
+
+        let cells = outline.fill();
+        let left = cells
+            .iter()
+            .map(|&(x, _)| x)
+            .min()
+            .ok_or("text has no visible cells")?;
+        let top = cells.iter().map(|&(_, y)| y).min().unwrap();
+        let right = cells.iter().map(|&(x, _)| x).max().unwrap();
+        let bottom = cells.iter().map(|&(_, y)| y).max().unwrap();
+
+        let mut pattern = Pattern::new(((right - left + 1) as usize, (bottom - top + 1) as usize));
+        for (x, y) in cells {
+            pattern.set(((x - left) as usize, (y - top) as usize), true);
+        }
+        Ok(pattern)
+    }
+}
+
+impl Font<'static> {
+    /// The bundled font, [`RUBIK`].
+    pub fn rubik() -> Self {
+        Font::new(RUBIK).expect("the bundled font is valid")
+    }
+}
+
+/// The outlines of glyphs as straight edges, measured in cells with y
+/// growing downwards.
+#[derive(Default)]
+struct Outline {
+    edges: Vec<((f32, f32), (f32, f32))>,
+    /// Cells per font unit.
+    scale: f32,
+    /// Where the current glyph's baseline starts.
+    origin: (f32, f32),
+    start: (f32, f32),
+    last: (f32, f32),
+}
+
+impl Outline {
+    fn point(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            self.origin.0 + x * self.scale,
+            self.origin.1 - y * self.scale,
+        )
+    }
+
+    fn edge_to(&mut self, to: (f32, f32)) {
+        self.edges.push((self.last, to));
+        self.last = to;
+    }
+
+    /// The cells whose centers are inside the outlines.
+    fn fill(&self) -> Vec<(i64, i64)> {
+        let mut cells = Vec::new();
+        if self.edges.is_empty() {
+            return cells;
+        }
+        let top = self
+            .edges
+            .iter()
+            .map(|e| e.0 .1.min(e.1 .1))
+            .fold(f32::MAX, f32::min);
+        let bottom = self
+            .edges
+            .iter()
+            .map(|e| e.0 .1.max(e.1 .1))
+            .fold(f32::MIN, f32::max);
+
+        let mut crossings = Vec::new();
+        for row in top.floor() as i64..=bottom.ceil() as i64 {
+            let y = row as f32 + 0.5;
+            crossings.clear();
+            for &((x0, y0), (x1, y1)) in &self.edges {
+                // each edge covers the rows from its upper end, but not its lower
+                let (upper, lower, winding) = if y0 < y1 { (y0, y1, 1) } else { (y1, y0, -1) };
+                if upper <= y && y < lower {
+                    crossings.push((x0 + (y - y0) / (y1 - y0) * (x1 - x0), winding));
+                }
+            }
+            crossings.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+            let mut winding = 0;
+            for pair in crossings.windows(2) {
+                winding += pair[0].1;
+                if winding != 0 {
+                    let first = (pair[0].0 - 0.5).ceil() as i64;
+                    let end = (pair[1].0 - 0.5).ceil() as i64;
+                    cells.extend((first..end).map(|column| (column, row)));
+                }
+            }
+        }
+        cells
+    }
+}
+
+impl OutlineBuilder for Outline {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.start = self.point(x, y);
+        self.last = self.start;
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        let to = self.point(x, y);
+        self.edge_to(to);
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let (from, control, to) = (self.last, self.point(x1, y1), self.point(x, y));
+        for step in 1..=CURVE_STEPS {
+            let t = step as f32 / CURVE_STEPS as f32;
+            let s = 1. - t;
+            let along = |a: f32, b: f32, c: f32| s * s * a + 2. * s * t * b + t * t * c;
+            self.edge_to((
+                along(from.0, control.0, to.0),
+                along(from.1, control.1, to.1),
+            ));
+        }
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let (from, first, second, to) = (
+            self.last,
+            self.point(x1, y1),
+            self.point(x2, y2),
+            self.point(x, y),
+        );
+        for step in 1..=CURVE_STEPS {
+            let t = step as f32 / CURVE_STEPS as f32;
+            let s = 1. - t;
+            let along = |a: f32, b: f32, c: f32, d: f32| {
+                s * s * s * a + 3. * s * s * t * b + 3. * s * t * t * c + t * t * t * d
+            };
+            self.edge_to((
+                along(from.0, first.0, second.0, to.0),
+                along(from.1, first.1, second.1, to.1),
+            ));
+        }
+    }
+
+    fn close(&mut self) {
+        let start = self.start;
+        self.edge_to(start);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn text_pattern() {
+        let font = Font::rubik();
+
+        // a capital I is a single bar
+        let i = font.pattern("I", 24).unwrap();
+        let (width, height) = i.size();
+        assert!(width < height / 3, "{:?}", i.size());
+        assert!((12..24).contains(&height), "{:?}", i.size());
+        assert_eq!(i.population(), width * height);
+
+        let spaced = font.pattern("I I", 24).unwrap();
+        assert_eq!(spaced.size().1, height);
+        assert_eq!(spaced.population(), 2 * i.population());
+
+        let lines = font.pattern("I\nI", 24).unwrap();
+        assert!(lines.size().1 > 24 + height / 2, "{:?}", lines.size());
+        assert_eq!(lines.population(), 2 * i.population());
+
+        // the hole in an O stays empty
+        let o = font.pattern("O", 24).unwrap();
+        let (width, height) = o.size();
+        assert!(!o.get((width / 2, height / 2)));
+        assert!(o.get((0, height / 2)));
+
+        assert!(font.pattern(" \n ", 24).is_err());
+        assert!(font.pattern("I", 0).is_err());
+        assert!(font.pattern(&"I".repeat(1_000_000), 16).is_err());
+        assert!(font.pattern(&"I\n".repeat(100_000), 1024).is_err());
+        assert!(Font::new(b"not a font").is_err());
+    }
+}